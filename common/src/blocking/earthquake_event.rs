@@ -1,139 +1,68 @@
-// Define a trait for earthquake data sources
-use serde::{Deserialize, Serialize}; // Import serde traits for serialization/deserialization
+use tokio::runtime::{Builder, Runtime};
 
-pub trait EarthquakeDataSource {
-    type Error;
+use crate::detail::EventDetail;
+pub use crate::earthquake_event::{Coordinates, EarthquakeEvent, Errors};
+use crate::earthquake_event::{CountableDataSource, EarthquakeDataSource};
+use crate::query::EventQuery;
 
-    fn fetch_earthquake_data(
-        &self,
-        format: &str,
-        start_time: &str,
-        end_time: &str,
-        min_magnitude: &str,
-    ) -> Result<Vec<EarthquakeEvent>, Self::Error>;
+/// Synchronous facade over an async [`EarthquakeDataSource`].
+///
+/// Requests are driven on a private current-thread runtime, so blocking callers go through
+/// exactly the same request and parsing code as async ones. It must not be used from within
+/// an async runtime.
+pub struct BlockingDataSource<S> {
+    source: S,
+    runtime: Runtime,
 }
 
-impl EarthquakeDataSource for UsgsDataSource {
-    type Error = Errors;
-
-    fn fetch_earthquake_data(
-        &self,
-        format: &str,
-        start_time: &str,
-        end_time: &str,
-        min_magnitude: &str,
-    ) -> Result<Vec<EarthquakeEvent>, Errors> {
-        // Construct the URL for the USGS API with the provided parameters
-        let base_url = "https://earthquake.usgs.gov/fdsnws/event/1/query";
-        let url = format!(
-            "{}?format={}&starttime={}&endtime={}&minmagnitude={}",
-            base_url, format, start_time, end_time, min_magnitude
-        );
-        println!("{:?}", url);
+// The USGS data source behind the blocking facade
+pub type UsgsDataSource = BlockingDataSource<crate::earthquake_event::UsgsDataSource>;
 
-        // Make the HTTP request to the USGS API
-        let response = reqwest::blocking::get(&url)?; // todo: async features: #[cfg(feature = "async")]
+impl<S> BlockingDataSource<S> {
+    pub fn new(source: S) -> Result<Self, Errors> {
+        let runtime = Builder::new_current_thread().enable_all().build()?;
+        Ok(Self { source, runtime })
+    }
 
-        // Check if the response was successful
-        match response.status() {
-            reqwest::StatusCode::OK => {
-                // Parse the GeoJSON response into EarthquakeEvent objects
-                let earthquake_data: GeoJsonData = response.json()?;
-                let earthquake_events: Vec<EarthquakeEvent> = earthquake_data
-                    .features
-                    .into_iter()
-                    .map(|feature| EarthquakeEvent {
-                        mag: feature.properties.mag,
-                        place: feature.properties.place,
-                        time: feature.properties.time,
-                        updated: feature.properties.updated,
-                        tsunami: feature.properties.tsunami,
-                        coordinates: feature.geometry.coordinates,
-                        mag_type: feature.properties.mag_type,
-                        event_type: feature.properties.event_type,
-                    })
-                    .collect();
-                Ok(earthquake_events)
-            }
-            status => Err(Errors::UnexpectedStatusCode(status.to_string())),
-        }
+    pub fn source(&self) -> &S {
+        &self.source
     }
 }
 
-#[derive(thiserror::Error, Debug)]
-pub enum Errors {
-    #[error("Unexpected status code: {0}")]
-    UnexpectedStatusCode(String),
-
-    #[error("request error")]
-    OtherError(#[from] reqwest::Error),
-}
+impl UsgsDataSource {
+    pub fn usgs() -> Result<Self, Errors> {
+        Self::new(crate::earthquake_event::UsgsDataSource::default())
+    }
 
-// Implement the trait for the USGS data source
-pub struct UsgsDataSource;
+    pub fn fetch_event_detail(&self, id: &str) -> Result<EventDetail, Errors> {
+        self.runtime.block_on(self.source.fetch_event_detail(id))
+    }
 
-// Data structure to hold earthquake event information
-#[derive(Debug, Serialize, Deserialize)]
-pub struct EarthquakeEvent {
-    pub mag: f64,
-    pub place: String,
-    pub time: i64,
-    pub updated: i64,
-    pub tsunami: i32,
-    pub coordinates: Vec<f64>,
-    pub mag_type: String,
-    pub event_type: String,
-}
+    pub fn catalogs(&self) -> Result<Vec<String>, Errors> {
+        self.runtime.block_on(self.source.catalogs())
+    }
 
-// GeoJSON data structure to deserialize the response
-#[derive(Debug, Serialize, Deserialize)]
-pub struct GeoJsonData {
-    features: Vec<Feature>,
-}
+    pub fn contributors(&self) -> Result<Vec<String>, Errors> {
+        self.runtime.block_on(self.source.contributors())
+    }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct Feature {
-    properties: Properties,
-    geometry: Geometry, // Add geometry field
-    id: String,
+    pub fn version(&self) -> Result<String, Errors> {
+        self.runtime.block_on(self.source.version())
+    }
 }
 
-// Define the Properties struct with all the fields
-#[derive(Debug, Serialize, Deserialize)]
-pub struct Properties {
-    mag: f64,
-    place: String,
-    time: i64,
-    updated: i64,
-    tz: Option<String>,
-    url: String,
-    detail: String,
-    felt: Option<i32>,
-    cdi: Option<f64>,
-    mmi: Option<f64>,
-    alert: Option<String>,
-    status: String,
-    tsunami: i32,
-    sig: i32,
-    net: String,
-    code: String,
-    ids: String,
-    sources: String,
-    types: String,
-    nst: Option<i32>,
-    dmin: Option<f64>,
-    rms: Option<f64>,
-    gap: Option<f64>,
-    #[serde(rename = "magType")]
-    mag_type: String,
-    #[serde(rename = "type")]
-    event_type: String,
-    title: String,
+impl<S: EarthquakeDataSource> BlockingDataSource<S> {
+    pub fn fetch_earthquake_data(
+        &self,
+        query: &EventQuery,
+    ) -> Result<Vec<EarthquakeEvent>, S::Error> {
+        self.runtime
+            .block_on(self.source.fetch_earthquake_data(query))
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct Geometry {
-    #[serde(rename = "type")]
-    type_: String,
-    coordinates: Vec<f64>,
+impl<S: CountableDataSource> BlockingDataSource<S> {
+    pub fn count_events(&self, query: &EventQuery) -> Result<u64, S::Error> {
+        self.runtime.block_on(self.source.count_events(query))
+    }
 }
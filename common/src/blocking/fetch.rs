@@ -1,22 +1,16 @@
 use super::earthquake_event::*;
+use crate::query::EventQuery;
 
 pub fn run_fetch(
     start_time: &str,
     end_time: &str,
     min_magnitude: i32,
 ) -> Result<Vec<EarthquakeEvent>, Errors> {
-    let usgs_data_source = UsgsDataSource;
-    let format = "geojson";
+    let usgs_data_source = UsgsDataSource::usgs()?;
+    let query = EventQuery::builder()
+        .time_range(start_time, end_time)?
+        .min_magnitude(min_magnitude as f64)
+        .build()?;
 
-    let usgs_earthquake_data = usgs_data_source.fetch_earthquake_data(
-        format,
-        start_time,
-        end_time,
-        &min_magnitude.to_string(),
-    );
-
-    match usgs_earthquake_data {
-        Ok(earthquake_events) => Ok(earthquake_events),
-        Err(e) => Err(e),
-    }
+    usgs_data_source.fetch_earthquake_data(&query)
 }
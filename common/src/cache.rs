@@ -0,0 +1,228 @@
+use std::env;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tracing::instrument;
+
+use crate::earthquake_event::{CountableDataSource, EarthquakeDataSource, EarthquakeEvent, Errors};
+use crate::query::EventQuery;
+
+/// Version of the cache entry format, covering the whole serialized event model.
+///
+/// Entries hold decoded events, including the derived region, place and proxy magnitude, so any
+/// change to how [`EarthquakeEvent`] (de)serializes or what is derived for it, including
+/// [`crate::coordinates::COORDINATES_VERSION`], must bump it. Entries of another version, or
+/// written before it was recorded, are ignored and fetched again.
+pub const CACHE_FORMAT_VERSION: u32 = 4;
+
+const CACHE_DIR_VAR: &str = "EARTHQUAKE_CACHE_DIR";
+const CACHE_TTL_VAR: &str = "EARTHQUAKE_CACHE_TTL_SECS";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CacheConfig {
+    pub directory: PathBuf,
+    // How long responses for windows that are still open stay fresh
+    pub ttl: Duration,
+    // Windows that ended this long before they were fetched are considered closed and never expire
+    pub settle_time: Duration,
+    // Serve only from the cache, never contacting the data source
+    pub offline: bool,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            directory: PathBuf::from(".cache/earthquakes"),
+            ttl: Duration::from_secs(60 * 60),
+            settle_time: Duration::from_secs(30 * 24 * 60 * 60),
+            offline: false,
+        }
+    }
+}
+
+impl CacheConfig {
+    /// Reads `EARTHQUAKE_CACHE_DIR` and `EARTHQUAKE_CACHE_TTL_SECS`, keeping the defaults for
+    /// unset or unparsable values.
+    pub fn from_env() -> Self {
+        let default = Self::default();
+        Self {
+            directory: env::var_os(CACHE_DIR_VAR)
+                .map(PathBuf::from)
+                .unwrap_or(default.directory),
+            ttl: env::var(CACHE_TTL_VAR)
+                .ok()
+                .and_then(|value| value.parse().ok())
+                .map(Duration::from_secs)
+                .unwrap_or(default.ttl),
+            ..default
+        }
+    }
+
+    pub fn offline(mut self, offline: bool) -> Self {
+        self.offline = offline;
+        self
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CacheEntry<T> {
+    query: String,
+    fetched_at: DateTime<Utc>,
+    // Entries of another format, or written before it was recorded, are ignored
+    #[serde(default)]
+    format_version: u32,
+    value: T,
+}
+
+/// Wraps a data source with an on-disk cache keyed by the normalized query.
+#[derive(Debug, Clone)]
+pub struct Cached<S> {
+    source: S,
+    config: CacheConfig,
+}
+
+impl<S> Cached<S> {
+    pub fn new(source: S, config: CacheConfig) -> Self {
+        Self { source, config }
+    }
+
+    pub fn source(&self) -> &S {
+        &self.source
+    }
+
+    // Returns the cached value, or `None` when it is missing, stale, unreadable or outdated
+    async fn read<T: DeserializeOwned>(&self, path: &Path, query: &EventQuery) -> Option<T> {
+        let contents = tokio::fs::read(path).await.ok()?;
+        let entry: CacheEntry<T> = match serde_json::from_slice(&contents) {
+            Ok(entry) => entry,
+            Err(error) => {
+                tracing::warn!(path = %path.display(), %error, "Ignoring unreadable cache entry");
+                return None;
+            }
+        };
+        if entry.format_version != CACHE_FORMAT_VERSION {
+            tracing::debug!(
+                path = %path.display(),
+                version = entry.format_version,
+                "Ignoring cache entry of another format version"
+            );
+            return None;
+        }
+
+        if self.config.offline || self.is_fresh(&entry, query) {
+            Some(entry.value)
+        } else {
+            None
+        }
+    }
+
+    fn is_fresh<T>(&self, entry: &CacheEntry<T>, query: &EventQuery) -> bool {
+        let settled = query.end_time().is_some_and(|end_time| {
+            entry.fetched_at - end_time
+                >= chrono::Duration::from_std(self.config.settle_time).unwrap_or_default()
+        });
+        let age = (Utc::now() - entry.fetched_at).to_std().unwrap_or_default();
+
+        settled || age < self.config.ttl
+    }
+
+    // Stores a fetched value; failing to do so costs only a later refetch, so it is just logged
+    async fn store<T: Serialize>(&self, path: &Path, key: String, value: &T) {
+        if let Err(error) = self.write(path, key, value).await {
+            tracing::warn!(path = %path.display(), %error, "Failed to write cache entry");
+        }
+    }
+
+    // Writes through a temporary file so readers never see a partial entry
+    async fn write<T: Serialize>(&self, path: &Path, key: String, value: &T) -> Result<(), Errors> {
+        let entry = CacheEntry {
+            query: key,
+            fetched_at: Utc::now(),
+            format_version: CACHE_FORMAT_VERSION,
+            value,
+        };
+        tokio::fs::create_dir_all(&self.config.directory).await?;
+        let temporary = path.with_extension("tmp");
+        tokio::fs::write(&temporary, serde_json::to_vec(&entry)?).await?;
+        tokio::fs::rename(&temporary, path).await?;
+        Ok(())
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        let digest = Sha256::digest(key.as_bytes());
+        let name = digest.iter().fold(String::new(), |mut name, byte| {
+            let _ = write!(name, "{byte:02x}");
+            name
+        });
+        self.config.directory.join(format!("{name}.json"))
+    }
+}
+
+// The query pairs come in a fixed order, so equal queries give equal keys
+fn cache_key(kind: &str, pairs: &[(&'static str, String)]) -> String {
+    let parameters: Vec<String> = pairs
+        .iter()
+        .map(|(name, value)| format!("{name}={value}"))
+        .collect();
+    format!("{kind}?{}", parameters.join("&"))
+}
+
+#[async_trait]
+impl<S> EarthquakeDataSource for Cached<S>
+where
+    S: EarthquakeDataSource<Error = Errors> + Sync,
+{
+    type Error = Errors;
+
+    #[instrument(skip_all, fields(cache_hit))]
+    async fn fetch_earthquake_data(
+        &self,
+        query: &EventQuery,
+    ) -> Result<Vec<EarthquakeEvent>, Errors> {
+        let key = cache_key("query", &query.to_query_pairs());
+        let path = self.path(&key);
+
+        let cached = self.read(&path, query).await;
+        tracing::Span::current().record("cache_hit", cached.is_some());
+        if let Some(events) = cached {
+            return Ok(events);
+        }
+        if self.config.offline {
+            return Err(Errors::CacheMiss(key));
+        }
+
+        let events = self.source.fetch_earthquake_data(query).await?;
+        self.store(&path, key, &events).await;
+        Ok(events)
+    }
+}
+
+#[async_trait]
+impl<S> CountableDataSource for Cached<S>
+where
+    S: CountableDataSource<Error = Errors> + Sync,
+{
+    #[instrument(skip_all, fields(cache_hit))]
+    async fn count_events(&self, query: &EventQuery) -> Result<u64, Errors> {
+        let key = cache_key("count", &query.to_count_pairs());
+        let path = self.path(&key);
+
+        let cached = self.read(&path, query).await;
+        tracing::Span::current().record("cache_hit", cached.is_some());
+        if let Some(count) = cached {
+            return Ok(count);
+        }
+        if self.config.offline {
+            return Err(Errors::CacheMiss(key));
+        }
+
+        let count = self.source.count_events(query).await?;
+        self.store(&path, key, &count).await;
+        Ok(count)
+    }
+}
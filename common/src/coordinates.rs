@@ -0,0 +1,135 @@
+use std::fmt;
+
+use serde::de::{self, MapAccess, SeqAccess, Visitor};
+use serde::ser::SerializeSeq;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Version of the coordinate (de)serialization.
+///
+/// Version 1 derived serde for `Coordinates` and decoded the GeoJSON position `[lon, lat, depth]`
+/// positionally into `lat, lon, depth`, so latitude and longitude were swapped. It serialized
+/// coordinates as a `{"lat", "lon", "depth"}` map. Version 2 reads and writes GeoJSON positions;
+/// data still in the version 1 map form is recognized and swapped back when read.
+///
+/// Cached events depend on it through [`crate::cache::CACHE_FORMAT_VERSION`]. Database rows keep latitude and longitude in columns of their own, so they do not
+/// depend on it.
+pub const COORDINATES_VERSION: u32 = 2;
+
+/// A point given as latitude and longitude in degrees and depth in kilometers.
+///
+/// (De)serializes as a GeoJSON position `[lon, lat, depth]`. Distances, bearings and the like
+/// are in [`crate::geodesy`].
+///
+/// Deserializing tells the two forms apart by the shape of the input, so it needs a
+/// self-describing format such as JSON; formats like bincode are not supported. Every map is
+/// taken for the version 1 form, with its `lat` and `lon` swapped, as no other writer of this
+/// crate produces maps. See [`COORDINATES_VERSION`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Coordinates<T> {
+    pub lat: T,
+    pub lon: T,
+    pub depth: T,
+}
+
+impl Coordinates<f64> {
+    /// Checks that latitude and longitude are in range and depth is finite.
+    pub fn validate(&self) -> Result<(), String> {
+        if !(-90.0..=90.0).contains(&self.lat) {
+            return Err(format!("latitude {} is outside [-90, 90]", self.lat));
+        }
+        if !(-180.0..=180.0).contains(&self.lon) {
+            return Err(format!("longitude {} is outside [-180, 180]", self.lon));
+        }
+        if !self.depth.is_finite() {
+            return Err(format!("depth {} is not finite", self.depth));
+        }
+        Ok(())
+    }
+}
+
+impl<T: Serialize> Serialize for Coordinates<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut position = serializer.serialize_seq(Some(3))?;
+        position.serialize_element(&self.lon)?;
+        position.serialize_element(&self.lat)?;
+        position.serialize_element(&self.depth)?;
+        position.end()
+    }
+}
+
+// Positions without a depth, such as polygon vertices, get a depth of 0
+impl<'de> Deserialize<'de> for Coordinates<f64> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_position(deserializer, false)
+    }
+}
+
+// Reads an event location, which must give the depth like in every other event format
+pub(crate) fn deserialize_hypocenter<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Coordinates<f64>, D::Error> {
+    deserialize_position(deserializer, true)
+}
+
+fn deserialize_position<'de, D: Deserializer<'de>>(
+    deserializer: D,
+    require_depth: bool,
+) -> Result<Coordinates<f64>, D::Error> {
+    let coordinates = deserializer.deserialize_any(CoordinatesVisitor { require_depth })?;
+    coordinates.validate().map_err(de::Error::custom)?;
+    Ok(coordinates)
+}
+
+struct CoordinatesVisitor {
+    require_depth: bool,
+}
+
+impl<'de> Visitor<'de> for CoordinatesVisitor {
+    type Value = Coordinates<f64>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a GeoJSON position [lon, lat, depth]")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let lon = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        let lat = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+        let depth = match seq.next_element()? {
+            Some(depth) => depth,
+            None if self.require_depth => return Err(de::Error::invalid_length(2, &self)),
+            // A GeoJSON position may omit the altitude
+            None => 0.0,
+        };
+        if seq.next_element::<de::IgnoredAny>()?.is_some() {
+            return Err(de::Error::invalid_length(4, &self));
+        }
+
+        Ok(Coordinates { lat, lon, depth })
+    }
+
+    // Version 1 map form, written with latitude and longitude swapped. Maps carry no version
+    // marker, so any map is read this way
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let (mut swapped_lat, mut swapped_lon, mut depth) = (None, None, None);
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "lat" => swapped_lat = Some(map.next_value()?),
+                "lon" => swapped_lon = Some(map.next_value()?),
+                "depth" => depth = Some(map.next_value()?),
+                _ => {
+                    map.next_value::<de::IgnoredAny>()?;
+                }
+            }
+        }
+
+        Ok(Coordinates {
+            lat: swapped_lon.ok_or_else(|| de::Error::missing_field("lon"))?,
+            lon: swapped_lat.ok_or_else(|| de::Error::missing_field("lat"))?,
+            depth: depth.ok_or_else(|| de::Error::missing_field("depth"))?,
+        })
+    }
+}
@@ -0,0 +1,280 @@
+use std::io::Read;
+
+use chrono::{DateTime, Utc};
+use csv::{ReaderBuilder, StringRecord};
+
+use crate::earthquake_event::{Coordinates, EarthquakeEvent, OriginQuality};
+use crate::place::PlaceInfo;
+use crate::regionalization::FlinnEngdahlRegion;
+use crate::types::{EventType, MagnitudeType, ReviewStatus};
+use crate::utils::parse_time;
+
+#[derive(thiserror::Error, Debug)]
+pub enum DelimitedError {
+    #[error(transparent)]
+    Csv(#[from] csv::Error),
+
+    #[error("missing column {0}")]
+    MissingColumn(&'static str),
+
+    #[error("line {line}: invalid {column} value {value:?}")]
+    InvalidValue {
+        line: u64,
+        column: &'static str,
+        value: String,
+    },
+
+    #[error("line {line}: {reason}")]
+    InvalidCoordinates { line: u64, reason: String },
+}
+
+/// Reads a USGS `format=csv` catalog.
+///
+/// Events without a magnitude get a `NaN` magnitude. Rows without a time, latitude, longitude
+/// or depth, or with coordinates out of range, fail the read.
+pub fn read_csv<R: Read>(reader: R) -> Result<Vec<EarthquakeEvent>, DelimitedError> {
+    let mut reader = ReaderBuilder::new().from_reader(reader);
+    let columns = Columns::new(reader.headers()?.clone());
+
+    let time = columns.index("time")?;
+    let latitude = columns.index("latitude")?;
+    let longitude = columns.index("longitude")?;
+    let depth = columns.index("depth")?;
+    let mag = columns.index("mag")?;
+    let mag_type = columns.index("magType")?;
+    let updated = columns.optional_index("updated");
+    let place = columns.optional_index("place");
+    let event_type = columns.optional_index("type");
+    let id = columns.optional_index("id");
+    let net = columns.optional_index("net");
+    let status = columns.optional_index("status");
+    let nst = columns.optional_index("nst");
+    let gap = columns.optional_index("gap");
+    let dmin = columns.optional_index("dmin");
+    let rms = columns.optional_index("rms");
+    let horizontal_error = columns.optional_index("horizontalError");
+    let depth_error = columns.optional_index("depthError");
+    let mag_error = columns.optional_index("magError");
+    let mag_nst = columns.optional_index("magNst");
+
+    let mut events = Vec::new();
+    for record in reader.records() {
+        let record = Record::new(record?);
+        let time = record.time(time, "time")?;
+        let id = record.string(id).unwrap_or_default();
+        let net = record.string(net);
+        let nst = record.optional_count(nst, "nst")?;
+        let gap = record.optional_number(gap, "gap")?;
+        let dmin = record.optional_number(dmin, "dmin")?;
+        let rms = record.optional_number(rms, "rms")?;
+        let description = record.string(place);
+        let coordinates = Coordinates {
+            lat: record.required_number(latitude, "latitude")?,
+            lon: record.required_number(longitude, "longitude")?,
+            depth: record.required_number(depth, "depth")?,
+        };
+        record.validate(&coordinates)?;
+
+        events.push(EarthquakeEvent {
+            // USGS ids are the network code followed by the event code
+            code: net
+                .as_deref()
+                .and_then(|net| id.strip_prefix(net))
+                .map(str::to_string),
+            id,
+            net,
+            mag: record.number(mag, "mag")?.unwrap_or(f64::NAN),
+            place_info: description.as_deref().and_then(PlaceInfo::parse),
+            place: description,
+            time,
+            updated: match updated {
+                Some(updated) => record.time(updated, "updated")?,
+                None => time,
+            },
+            region: FlinnEngdahlRegion::lookup(&coordinates),
+            coordinates,
+            mag_type: record.string(Some(mag_type)).map(MagnitudeType::from),
+            event_type: record
+                .string(event_type)
+                .map(EventType::from)
+                .unwrap_or_default(),
+            status: record.string(status).map(ReviewStatus::from),
+            nst: nst.map(|count| count as i32),
+            gap,
+            dmin,
+            rms,
+            quality: Some(OriginQuality {
+                horizontal_uncertainty_km: record
+                    .optional_number(horizontal_error, "horizontalError")?,
+                depth_uncertainty_km: record.optional_number(depth_error, "depthError")?,
+                used_station_count: nst,
+                standard_error: rms,
+                azimuthal_gap: gap,
+                minimum_distance_deg: dmin,
+                mag_uncertainty: record.optional_number(mag_error, "magError")?,
+                mag_station_count: record.optional_count(mag_nst, "magNst")?,
+                ..OriginQuality::default()
+            }),
+            ..EarthquakeEvent::default()
+        });
+    }
+
+    Ok(events)
+}
+
+/// Reads a pipe-delimited FDSN `format=text` catalog.
+///
+/// The text format carries no update time, so `updated` is set to the origin time. Events
+/// without a magnitude get a `NaN` magnitude. Rows without a time, latitude, longitude or
+/// depth, or with coordinates out of range, fail the read.
+pub fn read_text<R: Read>(reader: R) -> Result<Vec<EarthquakeEvent>, DelimitedError> {
+    let mut reader = ReaderBuilder::new()
+        .delimiter(b'|')
+        .flexible(true)
+        .from_reader(reader);
+    let columns = Columns::new(reader.headers()?.clone());
+
+    let time = columns.index("Time")?;
+    let latitude = columns.index("Latitude")?;
+    let longitude = columns.index("Longitude")?;
+    let depth = columns.index("Depth/km")?;
+    let id = columns.optional_index("EventID");
+    let catalog = columns.optional_index("Catalog");
+    let mag = columns.optional_index("Magnitude");
+    let mag_type = columns.optional_index("MagType");
+    let place = columns.optional_index("EventLocationName");
+    let event_type = columns.optional_index("EventType");
+
+    let mut events = Vec::new();
+    for record in reader.records() {
+        let record = Record::new(record?);
+        let time = record.time(time, "Time")?;
+        let description = record.string(place);
+        let coordinates = Coordinates {
+            lat: record.required_number(latitude, "Latitude")?,
+            lon: record.required_number(longitude, "Longitude")?,
+            depth: record.required_number(depth, "Depth/km")?,
+        };
+        record.validate(&coordinates)?;
+
+        events.push(EarthquakeEvent {
+            id: record.string(id).unwrap_or_default(),
+            net: record.string(catalog),
+            mag: record
+                .optional_number(mag, "Magnitude")?
+                .unwrap_or(f64::NAN),
+            place_info: description.as_deref().and_then(PlaceInfo::parse),
+            place: description,
+            time,
+            updated: time,
+            region: FlinnEngdahlRegion::lookup(&coordinates),
+            coordinates,
+            mag_type: record.string(mag_type).map(MagnitudeType::from),
+            event_type: record
+                .string(event_type)
+                .map(EventType::from)
+                .unwrap_or_default(),
+            ..EarthquakeEvent::default()
+        });
+    }
+
+    Ok(events)
+}
+
+// Header lookup tolerant of the leading '#' and padding used by FDSN text headers
+struct Columns {
+    headers: StringRecord,
+}
+
+impl Columns {
+    fn new(headers: StringRecord) -> Self {
+        Self { headers }
+    }
+
+    fn optional_index(&self, name: &str) -> Option<usize> {
+        self.headers
+            .iter()
+            .position(|header| header.trim().trim_start_matches('#').trim() == name)
+    }
+
+    fn index(&self, name: &'static str) -> Result<usize, DelimitedError> {
+        self.optional_index(name)
+            .ok_or(DelimitedError::MissingColumn(name))
+    }
+}
+
+struct Record {
+    record: StringRecord,
+}
+
+impl Record {
+    fn new(record: StringRecord) -> Self {
+        Self { record }
+    }
+
+    fn line(&self) -> u64 {
+        self.record
+            .position()
+            .map(|position| position.line())
+            .unwrap_or_default()
+    }
+
+    fn invalid(&self, column: &'static str, value: &str) -> DelimitedError {
+        DelimitedError::InvalidValue {
+            line: self.line(),
+            column,
+            value: value.to_string(),
+        }
+    }
+
+    fn validate(&self, coordinates: &Coordinates<f64>) -> Result<(), DelimitedError> {
+        coordinates
+            .validate()
+            .map_err(|reason| DelimitedError::InvalidCoordinates {
+                line: self.line(),
+                reason,
+            })
+    }
+
+    fn string(&self, index: Option<usize>) -> Option<String> {
+        index
+            .and_then(|index| self.record.get(index))
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(str::to_string)
+    }
+
+    fn time(&self, index: usize, column: &'static str) -> Result<DateTime<Utc>, DelimitedError> {
+        let value = self.record.get(index).unwrap_or_default().trim();
+        parse_time(value).ok_or_else(|| self.invalid(column, value))
+    }
+
+    fn number(&self, index: usize, column: &'static str) -> Result<Option<f64>, DelimitedError> {
+        self.optional_number(Some(index), column)
+    }
+
+    fn required_number(&self, index: usize, column: &'static str) -> Result<f64, DelimitedError> {
+        self.number(index, column)?
+            .ok_or_else(|| self.invalid(column, ""))
+    }
+
+    fn optional_number(
+        &self,
+        index: Option<usize>,
+        column: &'static str,
+    ) -> Result<Option<f64>, DelimitedError> {
+        self.string(index)
+            .map(|value| value.parse().map_err(|_| self.invalid(column, &value)))
+            .transpose()
+    }
+
+    fn optional_count(
+        &self,
+        index: Option<usize>,
+        column: &'static str,
+    ) -> Result<Option<u32>, DelimitedError> {
+        self.string(index)
+            .map(|value| value.parse().map_err(|_| self.invalid(column, &value)))
+            .transpose()
+    }
+}
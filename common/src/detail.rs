@@ -0,0 +1,293 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::earthquake_event::{EarthquakeEvent, Errors, Feature};
+use crate::types::{MagnitudeType, PagerAlert, ReviewStatus};
+
+/// An event with the products contributed to it, as returned by the USGS detail endpoint.
+#[derive(Debug, Clone)]
+pub struct EventDetail {
+    pub event: EarthquakeEvent,
+    pub products: Products,
+}
+
+/// Products of an event by type. Each list holds the preferred product first.
+#[derive(Debug, Clone, Default)]
+pub struct Products {
+    pub origin: Vec<Origin>,
+    pub phase_data: Vec<Origin>,
+    pub moment_tensor: Vec<MomentTensor>,
+    pub focal_mechanism: Vec<FocalMechanism>,
+    pub shakemap: Vec<ShakeMap>,
+    pub dyfi: Vec<Dyfi>,
+    pub losspager: Vec<LossPager>,
+    // Products of any other type, untyped
+    pub other: BTreeMap<String, Vec<Product>>,
+}
+
+impl Products {
+    pub fn preferred_moment_tensor(&self) -> Option<&MomentTensor> {
+        self.moment_tensor.first()
+    }
+
+    pub fn preferred_losspager(&self) -> Option<&LossPager> {
+        self.losspager.first()
+    }
+}
+
+/// Product metadata common to every product type.
+///
+/// Product properties are all strings; the typed products parse the ones they know.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Product {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub product_type: String,
+    pub code: String,
+    pub source: String,
+    pub status: String,
+    // Milliseconds since the epoch
+    pub update_time: i64,
+    #[serde(default)]
+    pub preferred_weight: i64,
+    #[serde(default)]
+    pub properties: BTreeMap<String, String>,
+    #[serde(default)]
+    pub contents: BTreeMap<String, Content>,
+}
+
+impl Product {
+    pub fn property(&self, name: &str) -> Option<&str> {
+        self.properties.get(name).map(String::as_str)
+    }
+
+    fn number(&self, name: &str) -> Option<f64> {
+        self.property(name)?.trim().parse().ok()
+    }
+
+    fn nodal_plane(&self, index: u8) -> Option<NodalPlane> {
+        Some(NodalPlane {
+            strike: self.number(&format!("nodal-plane-{index}-strike"))?,
+            dip: self.number(&format!("nodal-plane-{index}-dip"))?,
+            rake: self.number(&format!("nodal-plane-{index}-rake"))?,
+        })
+    }
+}
+
+// A file attached to a product, e.g. a QuakeML document or a ShakeMap grid
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Content {
+    pub content_type: String,
+    pub last_modified: i64,
+    pub length: u64,
+    pub url: Option<String>,
+}
+
+// An `origin` or `phase-data` product
+#[derive(Debug, Clone)]
+pub struct Origin {
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub depth_km: Option<f64>,
+    pub magnitude: Option<f64>,
+    pub magnitude_type: Option<MagnitudeType>,
+    pub review_status: Option<ReviewStatus>,
+    pub standard_error: Option<f64>,
+    pub azimuthal_gap: Option<f64>,
+    pub num_phases_used: Option<f64>,
+    pub product: Product,
+}
+
+impl From<Product> for Origin {
+    fn from(product: Product) -> Self {
+        Self {
+            latitude: product.number("latitude"),
+            longitude: product.number("longitude"),
+            depth_km: product.number("depth"),
+            magnitude: product.number("magnitude"),
+            magnitude_type: product.property("magnitude-type").map(MagnitudeType::from),
+            review_status: product.property("review-status").map(ReviewStatus::from),
+            standard_error: product.number("standard-error"),
+            azimuthal_gap: product.number("azimuthal-gap"),
+            num_phases_used: product.number("num-phases-used"),
+            product,
+        }
+    }
+}
+
+// Strike, dip and rake in degrees
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NodalPlane {
+    pub strike: f64,
+    pub dip: f64,
+    pub rake: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct MomentTensor {
+    pub derived_magnitude: Option<f64>,
+    pub derived_magnitude_type: Option<MagnitudeType>,
+    pub derived_depth_km: Option<f64>,
+    // Newton meters
+    pub scalar_moment: Option<f64>,
+    pub percent_double_couple: Option<f64>,
+    // Tensor components in Newton meters, in the r/theta/phi convention
+    pub mrr: Option<f64>,
+    pub mtt: Option<f64>,
+    pub mpp: Option<f64>,
+    pub mrt: Option<f64>,
+    pub mrp: Option<f64>,
+    pub mtp: Option<f64>,
+    pub nodal_planes: Vec<NodalPlane>,
+    pub product: Product,
+}
+
+impl From<Product> for MomentTensor {
+    fn from(product: Product) -> Self {
+        Self {
+            derived_magnitude: product.number("derived-magnitude"),
+            derived_magnitude_type: product
+                .property("derived-magnitude-type")
+                .map(MagnitudeType::from),
+            derived_depth_km: product.number("derived-depth"),
+            scalar_moment: product.number("scalar-moment"),
+            percent_double_couple: product.number("percent-double-couple"),
+            mrr: product.number("tensor-mrr"),
+            mtt: product.number("tensor-mtt"),
+            mpp: product.number("tensor-mpp"),
+            mrt: product.number("tensor-mrt"),
+            mrp: product.number("tensor-mrp"),
+            mtp: product.number("tensor-mtp"),
+            nodal_planes: (1..=2)
+                .filter_map(|index| product.nodal_plane(index))
+                .collect(),
+            product,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FocalMechanism {
+    pub nodal_planes: Vec<NodalPlane>,
+    pub product: Product,
+}
+
+impl From<Product> for FocalMechanism {
+    fn from(product: Product) -> Self {
+        Self {
+            nodal_planes: (1..=2)
+                .filter_map(|index| product.nodal_plane(index))
+                .collect(),
+            product,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ShakeMap {
+    pub max_mmi: Option<f64>,
+    // Peak ground acceleration in %g and velocity in cm/s
+    pub max_pga: Option<f64>,
+    pub max_pgv: Option<f64>,
+    pub map_status: Option<String>,
+    pub version: Option<String>,
+    pub product: Product,
+}
+
+impl From<Product> for ShakeMap {
+    fn from(product: Product) -> Self {
+        Self {
+            max_mmi: product.number("maxmmi"),
+            max_pga: product.number("maxpga"),
+            max_pgv: product.number("maxpgv"),
+            map_status: product.property("map-status").map(str::to_string),
+            version: product.property("version").map(str::to_string),
+            product,
+        }
+    }
+}
+
+// "Did You Feel It?" community intensity
+#[derive(Debug, Clone)]
+pub struct Dyfi {
+    pub max_mmi: Option<f64>,
+    pub num_responses: Option<u64>,
+    pub product: Product,
+}
+
+impl From<Product> for Dyfi {
+    fn from(product: Product) -> Self {
+        Self {
+            max_mmi: product.number("maxmmi"),
+            num_responses: product
+                .property("num-responses")
+                .or_else(|| product.property("numResp"))
+                .and_then(|value| value.trim().parse().ok()),
+            product,
+        }
+    }
+}
+
+// PAGER estimate of fatalities and economic losses
+#[derive(Debug, Clone)]
+pub struct LossPager {
+    pub alert_level: Option<PagerAlert>,
+    pub max_mmi: Option<f64>,
+    pub product: Product,
+}
+
+impl From<Product> for LossPager {
+    fn from(product: Product) -> Self {
+        Self {
+            alert_level: product.property("alertlevel").map(PagerAlert::from),
+            max_mmi: product.number("maxmmi"),
+            product,
+        }
+    }
+}
+
+/// Parses the GeoJSON feature returned for a single `eventid` query.
+pub fn parse_event_detail(body: &str) -> Result<EventDetail, Errors> {
+    let mut feature: serde_json::Value = serde_json::from_str(body)?;
+    let products = feature
+        .get_mut("properties")
+        .and_then(|properties| properties.as_object_mut())
+        .and_then(|properties| properties.remove("products"))
+        .unwrap_or_default();
+    let products: BTreeMap<String, Vec<Product>> = if products.is_null() {
+        BTreeMap::new()
+    } else {
+        serde_json::from_value(products)?
+    };
+    let event = EarthquakeEvent::from(serde_json::from_value::<Feature>(feature)?);
+
+    let mut typed = Products::default();
+    for (product_type, mut products) in products {
+        // The service lists products by descending preference, but sort to be safe
+        products.sort_by_key(|product| std::cmp::Reverse(product.preferred_weight));
+        match product_type.as_str() {
+            "origin" => typed.origin = convert(products),
+            "phase-data" => typed.phase_data = convert(products),
+            "moment-tensor" => typed.moment_tensor = convert(products),
+            "focal-mechanism" => typed.focal_mechanism = convert(products),
+            "shakemap" => typed.shakemap = convert(products),
+            "dyfi" => typed.dyfi = convert(products),
+            "losspager" => typed.losspager = convert(products),
+            _ => {
+                typed.other.insert(product_type, products);
+            }
+        }
+    }
+
+    Ok(EventDetail {
+        event,
+        products: typed,
+    })
+}
+
+fn convert<T: From<Product>>(products: Vec<Product>) -> Vec<T> {
+    products.into_iter().map(T::from).collect()
+}
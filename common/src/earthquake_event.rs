@@ -1,106 +1,634 @@
+use std::io::Read;
+use std::time::Duration;
+
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures::stream::BoxStream;
+use futures::{StreamExt, TryStreamExt};
 use serde::{Deserialize, Serialize};
 use tracing::{instrument, Span}; // Import serde traits for serialization/deserialization
 
+use crate::coordinates::deserialize_hypocenter;
+pub use crate::coordinates::Coordinates;
+use crate::delimited::{read_csv, read_text, DelimitedError};
+use crate::detail::{parse_event_detail, EventDetail};
+use crate::place::PlaceInfo;
+use crate::quakeml::{parse_quakeml, QuakeMlError};
+use crate::query::{EventQuery, Format, QueryError};
+use crate::regionalization::FlinnEngdahlRegion;
+use crate::retry::{is_retryable_status, RetryPolicy};
+use crate::stream::decode_geojson;
+use crate::types::{EventType, MagnitudeType, PagerAlert, ReviewStatus};
+use crate::utils::epoch_millis;
+
+/// Base URL of the USGS FDSN event web service.
+pub const USGS_BASE_URL: &str = "https://earthquake.usgs.gov/fdsnws/event/1/";
+
+pub(crate) const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
+pub(crate) const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+pub(crate) const DEFAULT_USER_AGENT: &str =
+    concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
+
 #[async_trait]
 pub trait EarthquakeDataSource {
     type Error;
 
     async fn fetch_earthquake_data(
         &self,
-        format: &str,
-        start_time: &str,
-        end_time: &str,
-        min_magnitude: &str,
+        query: &EventQuery,
     ) -> Result<Vec<EarthquakeEvent>, Self::Error>;
 }
 
+// Data sources that can report how many events match a query without fetching them
+#[async_trait]
+pub trait CountableDataSource: EarthquakeDataSource {
+    async fn count_events(&self, query: &EventQuery) -> Result<u64, Self::Error>;
+}
+
 #[async_trait]
 impl EarthquakeDataSource for UsgsDataSource {
     type Error = Errors;
 
-    #[instrument(skip(self), fields(url))]
+    #[instrument(skip(self), fields(url, attempts))]
     async fn fetch_earthquake_data(
         &self,
-        format: &str,
-        start_time: &str,
-        end_time: &str,
-        min_magnitude: &str,
+        query: &EventQuery,
     ) -> Result<Vec<EarthquakeEvent>, Errors> {
-        // Construct the URL for the USGS API with the provided parameters
-        let base_url = "https://earthquake.usgs.gov/fdsnws/event/1/query";
-        let url = format!(
-            "{}?format={}&starttime={}&endtime={}&minmagnitude={}",
-            base_url, format, start_time, end_time, min_magnitude
-        );
-        Span::current().record("url", &url);
+        // Construct the URL for the USGS API from the query parameters
+        let request = self
+            .client
+            .get(self.endpoint("query")?)
+            .query(&query.to_query_pairs())
+            .timeout(self.timeout)
+            .build()?;
+        Span::current().record("url", request.url().as_str());
         tracing::info!("Fetching");
 
-        // Make the HTTP request to the USGS API
-        let response = reqwest::get(&url).await?; // todo: async features: #[cfg(feature = "async")]
-
-        // Check if the response was successful
-        match response.status() {
-            reqwest::StatusCode::OK => {
-                // Parse the GeoJSON response into EarthquakeEvent objects
-                let earthquake_data: GeoJsonData = response.json().await?;
-                let earthquake_events: Vec<EarthquakeEvent> = earthquake_data
-                    .features
-                    .into_iter()
-                    .map(|feature: Feature| EarthquakeEvent {
-                        mag: feature.properties.mag,
-                        place: feature.properties.place,
-                        time: feature.properties.time,
-                        updated: feature.properties.updated,
-                        tsunami: feature.properties.tsunami,
-                        coordinates: feature.geometry.coordinates,
-                        mag_type: feature.properties.mag_type,
-                        event_type: feature.properties.event_type,
-                    })
-                    .collect();
-                Ok(earthquake_events)
-            }
-            status => Err(Errors::UnexpectedStatusCode(status.to_string())),
+        // Make the HTTP request to the USGS API and check if the response was successful, then
+        // parse the response body in the requested format into EarthquakeEvent objects. GeoJSON
+        // is decoded as it arrives so the whole body is never held in memory.
+        self.retry_policy
+            .execute_with(&self.client, request, |response| async move {
+                let response = successful(response).await?;
+                if query.format() == Format::GeoJson {
+                    return decode_geojson(response.bytes_stream()).try_collect().await;
+                }
+                let body = response.text().await?;
+                parse_events(query.format(), &body)
+            })
+            .await
+    }
+}
+
+#[async_trait]
+impl CountableDataSource for UsgsDataSource {
+    #[instrument(skip(self), fields(url, attempts))]
+    async fn count_events(&self, query: &EventQuery) -> Result<u64, Errors> {
+        let request = self
+            .client
+            .get(self.endpoint("count")?)
+            .query(&query.to_count_pairs())
+            .timeout(self.timeout)
+            .build()?;
+        Span::current().record("url", request.url().as_str());
+        tracing::info!("Counting");
+
+        // Without a format parameter the count endpoint answers with a plain number
+        let body = self
+            .retry_policy
+            .execute_with(&self.client, request, successful_text)
+            .await?;
+        body.trim()
+            .parse()
+            .map_err(|_| Errors::UnexpectedCountResponse(body))
+    }
+}
+
+// Maps unsuccessful responses to errors, keeping the error text the service puts in the body
+pub(crate) async fn successful(response: reqwest::Response) -> Result<reqwest::Response, Errors> {
+    match response.status() {
+        reqwest::StatusCode::OK => Ok(response),
+        reqwest::StatusCode::NO_CONTENT => Err(Errors::NoData),
+        status => Err(Errors::Status {
+            status,
+            body: response
+                .text()
+                .await
+                .map(|body| body.trim().to_string())
+                .unwrap_or_default(),
+        }),
+    }
+}
+
+// The body of a successful response
+pub(crate) async fn successful_text(response: reqwest::Response) -> Result<String, Errors> {
+    Ok(successful(response).await?.text().await?)
+}
+
+/// Parses a response body of the given format into earthquake events.
+pub fn parse_events(format: Format, body: &str) -> Result<Vec<EarthquakeEvent>, Errors> {
+    read_events(format, body.as_bytes())
+}
+
+/// Reads earthquake events of the given format, e.g. from a local catalog file.
+pub fn read_events<R: Read>(format: Format, mut reader: R) -> Result<Vec<EarthquakeEvent>, Errors> {
+    match format {
+        Format::GeoJson => {
+            // Features are decoded one by one so a failure can name the offending feature
+            let earthquake_data: GeoJsonData = serde_json::from_reader(reader)?;
+            earthquake_data
+                .features
+                .into_iter()
+                .enumerate()
+                .map(|(index, feature)| decode_feature(index, feature))
+                .collect()
         }
+        Format::Xml => {
+            let mut xml = String::new();
+            reader.read_to_string(&mut xml)?;
+            Ok(parse_quakeml(&xml)?)
+        }
+        Format::Csv => Ok(read_csv(reader)?),
+        Format::Text => Ok(read_text(reader)?),
     }
 }
 
+// Decodes the feature at `index` of a FeatureCollection, naming it in the error
+pub(crate) fn decode_feature(
+    index: usize,
+    feature: serde_json::Value,
+) -> Result<EarthquakeEvent, Errors> {
+    let id = feature
+        .get("id")
+        .and_then(serde_json::Value::as_str)
+        .map(str::to_string);
+    serde_json::from_value::<Feature>(feature)
+        .map(EarthquakeEvent::from)
+        .map_err(|source| Errors::Decode { index, id, source })
+}
+
 #[derive(thiserror::Error, Debug)]
 pub enum Errors {
-    #[error("Unexpected status code: {0}")]
-    UnexpectedStatusCode(String),
+    #[error("HTTP {status}: {body}")]
+    Status {
+        status: reqwest::StatusCode,
+        body: String,
+    },
+
+    #[error("no data matches the query")]
+    NoData,
+
+    #[error("request timed out: {0}")]
+    Timeout(#[source] reqwest::Error),
+
+    #[error("invalid query: {0}")]
+    InvalidQuery(#[from] QueryError),
+
+    #[error("Unexpected count response: {0}")]
+    UnexpectedCountResponse(String),
 
-    #[error("request error")]
-    OtherError(#[from] reqwest::Error),
+    #[error("{count} events between {start_time} and {end_time} exceed the result cap and the window cannot be split further")]
+    WindowTooDense {
+        count: u64,
+        start_time: DateTime<Utc>,
+        end_time: DateTime<Utc>,
+    },
+
+    #[error("invalid GeoJSON: {0}")]
+    GeoJson(#[from] serde_json::Error),
+
+    #[error("invalid GeoJSON feature {index} ({}): {source}", id.as_deref().unwrap_or("without id"))]
+    Decode {
+        index: usize,
+        id: Option<String>,
+        source: serde_json::Error,
+    },
+
+    #[error("invalid QuakeML: {0}")]
+    QuakeMl(#[from] QuakeMlError),
+
+    #[error("invalid CSV or text catalog: {0}")]
+    Delimited(#[from] DelimitedError),
+
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("invalid base URL: {0}")]
+    InvalidBaseUrl(String),
+
+    #[error("unknown catalog file format: {}", .0.display())]
+    UnknownFormat(std::path::PathBuf),
+
+    #[error("{}: {source}", path.display())]
+    File {
+        path: std::path::PathBuf,
+        source: Box<Errors>,
+    },
+
+    #[error("invalid XML document: {0}")]
+    Xml(#[from] roxmltree::Error),
+
+    #[error("parameters not supported by the service: {}", .0.join(", "))]
+    UnsupportedParameters(Vec<String>),
+
+    #[error("not in the cache while offline: {0}")]
+    CacheMiss(String),
+
+    #[error("request error: {0}")]
+    Request(#[source] reqwest::Error),
+}
+
+impl Errors {
+    /// Whether the same request may succeed when repeated later, the rule
+    /// [`RetryPolicy`] retries by.
+    ///
+    /// Timeouts, failed connections, bodies that break off and the statuses 429, 500, 502, 503 and
+    /// 504 are transient. Other statuses such as 501 or 505, and builder, redirect and decode
+    /// errors, would fail the same way again.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Errors::Timeout(_) => true,
+            Errors::Status { status, .. } => is_retryable_status(*status),
+            Errors::Request(error) => error.is_connect() || error.is_request() || error.is_body(),
+            _ => false,
+        }
+    }
+}
+
+impl From<reqwest::Error> for Errors {
+    fn from(error: reqwest::Error) -> Self {
+        if error.is_timeout() {
+            Errors::Timeout(error)
+        } else {
+            Errors::Request(error)
+        }
+    }
 }
 
 // Implement the trait for the USGS data source
-pub struct UsgsDataSource;
+#[derive(Debug, Clone)]
+pub struct UsgsDataSource {
+    client: reqwest::Client,
+    base_url: reqwest::Url,
+    timeout: Duration,
+    retry_policy: RetryPolicy,
+}
+
+impl UsgsDataSource {
+    pub fn builder() -> UsgsDataSourceBuilder {
+        UsgsDataSourceBuilder::default()
+    }
+
+    pub fn base_url(&self) -> &reqwest::Url {
+        &self.base_url
+    }
+
+    /// Fetches the events matching `query` as a stream that yields each event as soon as it
+    /// has been received, so processing can start before the download finishes.
+    ///
+    /// The events are always requested as GeoJSON, whatever the format of `query`.
+    #[instrument(skip(self), fields(url, attempts))]
+    pub async fn stream_earthquake_data(
+        &self,
+        query: &EventQuery,
+    ) -> Result<BoxStream<'static, Result<EarthquakeEvent, Errors>>, Errors> {
+        let mut pairs = query.to_query_pairs();
+        for (name, value) in &mut pairs {
+            if *name == "format" {
+                *value = Format::GeoJson.as_str().to_string();
+            }
+        }
+        let request = self
+            .client
+            .get(self.endpoint("query")?)
+            .query(&pairs)
+            .timeout(self.timeout)
+            .build()?;
+        Span::current().record("url", request.url().as_str());
+        tracing::info!("Streaming");
+
+        // Only getting the response is retried, the events are passed on as they arrive
+        let response = self.retry_policy.execute(&self.client, request).await?;
+        let response = successful(response).await?;
+        Ok(decode_geojson(response.bytes_stream()).boxed())
+    }
+
+    /// Catalogs the service can be queried for with `catalog=`.
+    pub async fn catalogs(&self) -> Result<Vec<String>, Errors> {
+        let body = self.get_text("catalogs").await?;
+        element_texts(&body, "Catalog")
+    }
+
+    /// Contributors the service can be queried for with `contributor=`.
+    pub async fn contributors(&self) -> Result<Vec<String>, Errors> {
+        let body = self.get_text("contributors").await?;
+        element_texts(&body, "Contributor")
+    }
+
+    /// Version of the service software.
+    pub async fn version(&self) -> Result<String, Errors> {
+        Ok(self.get_text("version").await?.trim().to_string())
+    }
+
+    #[instrument(skip(self), fields(url, attempts))]
+    async fn get_text(&self, endpoint: &str) -> Result<String, Errors> {
+        let request = self
+            .client
+            .get(self.endpoint(endpoint)?)
+            .timeout(self.timeout)
+            .build()?;
+        Span::current().record("url", request.url().as_str());
+
+        self.retry_policy
+            .execute_with(&self.client, request, successful_text)
+            .await
+    }
+
+    /// Fetches an event by id together with its products, such as moment tensors and the
+    /// PAGER alert level.
+    #[instrument(skip(self), fields(url, attempts))]
+    pub async fn fetch_event_detail(&self, id: &str) -> Result<EventDetail, Errors> {
+        let request = self
+            .client
+            .get(self.endpoint("query")?)
+            .query(&[("eventid", id), ("format", Format::GeoJson.as_str())])
+            .timeout(self.timeout)
+            .build()?;
+        Span::current().record("url", request.url().as_str());
+        tracing::info!("Fetching detail");
+
+        let body = self
+            .retry_policy
+            .execute_with(&self.client, request, successful_text)
+            .await?;
+        parse_event_detail(&body)
+    }
+
+    // Resolves an FDSN endpoint such as `query` or `count` against the base URL
+    fn endpoint(&self, name: &str) -> Result<reqwest::Url, Errors> {
+        self.base_url
+            .join(name)
+            .map_err(|error| Errors::InvalidBaseUrl(error.to_string()))
+    }
+}
+
+impl Default for UsgsDataSource {
+    fn default() -> Self {
+        Self::builder()
+            .build()
+            .expect("the default USGS data source configuration is valid")
+    }
+}
+
+/// Configures a [`UsgsDataSource`].
+///
+/// `user_agent` and `connect_timeout` only apply to the client built by the builder; pass a
+/// preconfigured `reqwest::Client` through `client` for proxies or custom pooling. The request
+/// `timeout` applies either way.
+#[derive(Debug)]
+pub struct UsgsDataSourceBuilder {
+    client: Option<reqwest::Client>,
+    base_url: String,
+    timeout: Duration,
+    connect_timeout: Duration,
+    user_agent: String,
+    retry_policy: RetryPolicy,
+}
+
+impl Default for UsgsDataSourceBuilder {
+    fn default() -> Self {
+        Self {
+            client: None,
+            base_url: USGS_BASE_URL.to_string(),
+            timeout: DEFAULT_TIMEOUT,
+            connect_timeout: DEFAULT_CONNECT_TIMEOUT,
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            retry_policy: RetryPolicy::default(),
+        }
+    }
+}
+
+impl UsgsDataSourceBuilder {
+    pub fn client(mut self, client: reqwest::Client) -> Self {
+        self.client = Some(client);
+        self
+    }
+
+    // Base URL of an FDSN event service, e.g. an internal mirror or a local mock server
+    pub fn base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn connect_timeout(mut self, connect_timeout: Duration) -> Self {
+        self.connect_timeout = connect_timeout;
+        self
+    }
+
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = user_agent.into();
+        self
+    }
+
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    pub fn build(self) -> Result<UsgsDataSource, Errors> {
+        let base_url = parse_base_url(self.base_url)?;
+        let client = match self.client {
+            Some(client) => client,
+            None => build_client(self.user_agent, self.connect_timeout)?,
+        };
+
+        Ok(UsgsDataSource {
+            client,
+            base_url,
+            timeout: self.timeout,
+            retry_policy: self.retry_policy,
+        })
+    }
+}
+
+// Texts of the elements named `name`, e.g. the `<Catalog>` entries of the catalogs document
+fn element_texts(xml: &str, name: &str) -> Result<Vec<String>, Errors> {
+    let document = roxmltree::Document::parse(xml)?;
+    Ok(document
+        .descendants()
+        .filter(|node| node.tag_name().name() == name)
+        .filter_map(|node| node.text())
+        .map(|text| text.trim().to_string())
+        .filter(|text| !text.is_empty())
+        .collect())
+}
+
+// Without a trailing slash Url::join would replace the last path segment
+pub(crate) fn parse_base_url(mut base_url: String) -> Result<reqwest::Url, Errors> {
+    if !base_url.ends_with('/') {
+        base_url.push('/');
+    }
+    reqwest::Url::parse(&base_url)
+        .map_err(|error| Errors::InvalidBaseUrl(format!("{base_url}: {error}")))
+}
+
+pub(crate) fn build_client(
+    user_agent: String,
+    connect_timeout: Duration,
+) -> Result<reqwest::Client, Errors> {
+    Ok(reqwest::Client::builder()
+        .user_agent(user_agent)
+        .connect_timeout(connect_timeout)
+        .build()?)
+}
 
 // Data structure to hold earthquake event information
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct EarthquakeEvent {
+    #[serde(default)]
+    pub id: String,
+    // Events without a magnitude carry NaN, which JSON writes as null
+    #[serde(deserialize_with = "nan_if_null")]
     pub mag: f64,
     pub place: Option<String>,
-    pub time: i64,
-    pub updated: i64,
+    // `place` taken apart, if it has one of the usual forms
+    #[serde(default)]
+    pub place_info: Option<PlaceInfo>,
+    // Millisecond precision; (de)serialized as epoch milliseconds, also read from ISO 8601
+    #[serde(with = "epoch_millis")]
+    pub time: DateTime<Utc>,
+    #[serde(with = "epoch_millis")]
+    pub updated: DateTime<Utc>,
     pub tsunami: i32,
     pub coordinates: Coordinates<f64>,
-    pub mag_type: String,
-    pub event_type: String,
+    // Flinn-Engdahl region of the epicenter
+    #[serde(default)]
+    pub region: Option<FlinnEngdahlRegion>,
+    // None when the catalog gives no magnitude type
+    pub mag_type: Option<MagnitudeType>,
+    pub event_type: EventType,
+    pub status: Option<ReviewStatus>,
+    pub sig: Option<i32>,
+    pub felt: Option<i32>,
+    pub cdi: Option<f64>,
+    pub mmi: Option<f64>,
+    pub alert: Option<PagerAlert>,
+    pub net: Option<String>,
+    pub code: Option<String>,
+    // Ids and sources of every contributing network, the preferred one included
+    #[serde(default)]
+    pub ids: Vec<String>,
+    #[serde(default)]
+    pub sources: Vec<String>,
+    pub nst: Option<i32>,
+    pub dmin: Option<f64>,
+    pub rms: Option<f64>,
+    pub gap: Option<f64>,
+    pub url: Option<String>,
+    // USGS detail feed of the event, see UsgsDataSource::fetch_event_detail
+    pub detail: Option<String>,
+    pub title: Option<String>,
+    pub quality: Option<OriginQuality>,
+    // Proxy moment magnitude, set by crate::homogenize; `mag` keeps the reported magnitude
+    pub mw_proxy: Option<f64>,
+}
+
+impl EarthquakeEvent {
+    /// The proxy moment magnitude, or `None` if the event has not been homogenized or no
+    /// conversion covers its magnitude type and value.
+    ///
+    /// The reported magnitude is deliberately not used in its place: it is on another scale and
+    /// would skew anything comparing homogenized magnitudes.
+    pub fn homogenized_mag(&self) -> Option<f64> {
+        self.mw_proxy
+    }
+}
+
+fn nan_if_null<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+    Ok(Option::<f64>::deserialize(deserializer)?.unwrap_or(f64::NAN))
+}
+
+impl From<Feature> for EarthquakeEvent {
+    fn from(feature: Feature) -> Self {
+        EarthquakeEvent {
+            id: feature.id,
+            mag: feature.properties.mag.unwrap_or(f64::NAN),
+            place_info: feature
+                .properties
+                .place
+                .as_deref()
+                .and_then(PlaceInfo::parse),
+            place: feature.properties.place,
+            time: feature.properties.time,
+            updated: feature.properties.updated,
+            tsunami: feature.properties.tsunami,
+            region: FlinnEngdahlRegion::lookup(&feature.geometry.coordinates),
+            coordinates: feature.geometry.coordinates,
+            mag_type: feature
+                .properties
+                .mag_type
+                .filter(|mag_type| !mag_type.trim().is_empty())
+                .map(MagnitudeType::from),
+            event_type: feature.properties.event_type.into(),
+            status: Some(feature.properties.status.into()),
+            sig: Some(feature.properties.sig),
+            felt: feature.properties.felt,
+            cdi: feature.properties.cdi,
+            mmi: feature.properties.mmi,
+            alert: feature.properties.alert.map(PagerAlert::from),
+            net: Some(feature.properties.net),
+            code: Some(feature.properties.code),
+            ids: split_list(&feature.properties.ids),
+            sources: split_list(&feature.properties.sources),
+            nst: feature.properties.nst,
+            dmin: feature.properties.dmin,
+            rms: feature.properties.rms,
+            gap: feature.properties.gap,
+            url: Some(feature.properties.url),
+            detail: feature.properties.detail,
+            title: Some(feature.properties.title),
+            quality: None,
+            mw_proxy: None,
+        }
+    }
+}
+
+// USGS joins ids, sources and product types as ",us1,ci2,"
+pub(crate) fn split_list(list: &str) -> Vec<String> {
+    list.split(',')
+        .filter(|item| !item.is_empty())
+        .map(str::to_string)
+        .collect()
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct Coordinates<T> {
-    pub lat: T,
-    pub lon: T,
-    pub depth: T,
+// Origin uncertainties and arrival counts, available from QuakeML and CSV responses
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct OriginQuality {
+    pub time_uncertainty_s: Option<f64>,
+    pub latitude_uncertainty_deg: Option<f64>,
+    pub longitude_uncertainty_deg: Option<f64>,
+    pub horizontal_uncertainty_km: Option<f64>,
+    pub depth_uncertainty_km: Option<f64>,
+    pub associated_phase_count: Option<u32>,
+    pub used_phase_count: Option<u32>,
+    pub associated_station_count: Option<u32>,
+    pub used_station_count: Option<u32>,
+    pub standard_error: Option<f64>,
+    pub azimuthal_gap: Option<f64>,
+    pub minimum_distance_deg: Option<f64>,
+    pub mag_uncertainty: Option<f64>,
+    pub mag_station_count: Option<u32>,
 }
 
 // GeoJSON data structure to deserialize the response
 #[derive(Debug, Serialize, Deserialize)]
 pub struct GeoJsonData {
-    features: Vec<Feature>,
+    features: Vec<serde_json::Value>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -114,13 +642,16 @@ pub struct Feature {
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Properties {
-    mag: f64,
+    // USGS sends null for events that have no magnitude yet, often with a null magType
+    mag: Option<f64>,
     place: Option<String>,
-    time: i64,
-    updated: i64,
+    #[serde(with = "epoch_millis")]
+    time: DateTime<Utc>,
+    #[serde(with = "epoch_millis")]
+    updated: DateTime<Utc>,
     tz: Option<String>,
     url: String,
-    detail: String,
+    detail: Option<String>,
     felt: Option<i32>,
     cdi: Option<f64>,
     mmi: Option<f64>,
@@ -137,7 +668,7 @@ pub struct Properties {
     dmin: Option<f64>,
     rms: Option<f64>,
     gap: Option<f64>,
-    mag_type: String,
+    mag_type: Option<String>,
     #[serde(alias = "type")]
     event_type: String,
     title: String,
@@ -147,5 +678,6 @@ pub struct Properties {
 pub struct Geometry {
     #[serde(alias = "type")]
     geometry_type: String,
+    #[serde(deserialize_with = "deserialize_hypocenter")]
     coordinates: Coordinates<f64>,
 }
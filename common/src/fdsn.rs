@@ -0,0 +1,313 @@
+use std::collections::BTreeSet;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use tracing::{instrument, Span};
+
+use crate::earthquake_event::{
+    build_client, parse_base_url, parse_events, successful_text, EarthquakeDataSource,
+    EarthquakeEvent, Errors, DEFAULT_CONNECT_TIMEOUT, DEFAULT_TIMEOUT, DEFAULT_USER_AGENT,
+};
+use crate::geodesy::EARTH_RADIUS_KM;
+use crate::query::{EventQuery, Format};
+use crate::retry::RetryPolicy;
+
+/// European-Mediterranean Seismological Centre.
+pub const EMSC_BASE_URL: &str = "https://www.seismicportal.eu/fdsnws/event/1/";
+/// GFZ German Research Centre for Geosciences (GEOFON).
+pub const GFZ_BASE_URL: &str = "https://geofon.gfz-potsdam.de/fdsnws/event/1/";
+/// Istituto Nazionale di Geofisica e Vulcanologia.
+pub const INGV_BASE_URL: &str = "https://webservices.ingv.it/fdsnws/event/1/";
+/// EarthScope (formerly IRIS) Data Services.
+pub const IRIS_BASE_URL: &str = "https://service.iris.edu/fdsnws/event/1/";
+
+// Kilometers per degree of arc on a spherical Earth
+const KM_PER_DEGREE: f64 = EARTH_RADIUS_KM * std::f64::consts::PI / 180.0;
+
+/// Client for any FDSN-WS event service.
+///
+/// Only the standard query parameters are sent. The standard formats are QuakeML and text, so
+/// queries asking for GeoJSON or CSV, which are USGS extensions, are answered in the source's
+/// own format (text unless configured otherwise).
+#[derive(Debug, Clone)]
+pub struct FdsnDataSource {
+    client: reqwest::Client,
+    base_url: reqwest::Url,
+    timeout: Duration,
+    retry_policy: RetryPolicy,
+    format: Format,
+}
+
+impl FdsnDataSource {
+    pub fn new(base_url: impl Into<String>) -> Result<Self, Errors> {
+        Self::builder(base_url).build()
+    }
+
+    pub fn builder(base_url: impl Into<String>) -> FdsnDataSourceBuilder {
+        FdsnDataSourceBuilder::new(base_url.into())
+    }
+
+    pub fn base_url(&self) -> &reqwest::Url {
+        &self.base_url
+    }
+
+    /// Reads the service's `application.wadl` to find out which parameters and formats it
+    /// supports.
+    #[instrument(skip(self), fields(url, attempts))]
+    pub async fn capabilities(&self) -> Result<FdsnCapabilities, Errors> {
+        let request = self
+            .client
+            .get(self.endpoint("application.wadl")?)
+            .timeout(self.timeout)
+            .build()?;
+        Span::current().record("url", request.url().as_str());
+
+        let body = self
+            .retry_policy
+            .execute_with(&self.client, request, successful_text)
+            .await?;
+        FdsnCapabilities::from_wadl(&body)
+    }
+
+    /// Fails with [`Errors::UnsupportedParameters`] if the service does not support every
+    /// parameter `query` would send.
+    pub async fn check_query(&self, query: &EventQuery) -> Result<(), Errors> {
+        let unsupported = self
+            .capabilities()
+            .await?
+            .unsupported_parameters(&self.query_pairs(query));
+
+        if unsupported.is_empty() {
+            Ok(())
+        } else {
+            Err(Errors::UnsupportedParameters(unsupported))
+        }
+    }
+
+    fn format_for(&self, query: &EventQuery) -> Format {
+        match query.format() {
+            format @ (Format::Xml | Format::Text) => format,
+            Format::GeoJson | Format::Csv => self.format,
+        }
+    }
+
+    // Standard parameters: the format replaced and the radius given in degrees
+    fn query_pairs(&self, query: &EventQuery) -> Vec<(&'static str, String)> {
+        let format = self.format_for(query);
+        query
+            .to_query_pairs()
+            .into_iter()
+            .map(|(name, value)| match name {
+                "format" => (name, format.as_str().to_string()),
+                "minradiuskm" | "maxradiuskm" => {
+                    let kilometers: f64 = value.parse().unwrap_or_default();
+                    let name = if name == "minradiuskm" {
+                        "minradius"
+                    } else {
+                        "maxradius"
+                    };
+                    (name, (kilometers / KM_PER_DEGREE).to_string())
+                }
+                _ => (name, value),
+            })
+            .collect()
+    }
+
+    fn endpoint(&self, name: &str) -> Result<reqwest::Url, Errors> {
+        self.base_url
+            .join(name)
+            .map_err(|error| Errors::InvalidBaseUrl(error.to_string()))
+    }
+}
+
+#[async_trait]
+impl EarthquakeDataSource for FdsnDataSource {
+    type Error = Errors;
+
+    #[instrument(skip(self), fields(url, attempts))]
+    async fn fetch_earthquake_data(
+        &self,
+        query: &EventQuery,
+    ) -> Result<Vec<EarthquakeEvent>, Errors> {
+        let request = self
+            .client
+            .get(self.endpoint("query")?)
+            .query(&self.query_pairs(query))
+            .timeout(self.timeout)
+            .build()?;
+        Span::current().record("url", request.url().as_str());
+        tracing::info!("Fetching");
+
+        let body = self
+            .retry_policy
+            .execute_with(&self.client, request, successful_text)
+            .await?;
+        parse_events(self.format_for(query), &body)
+    }
+}
+
+/// Configures an [`FdsnDataSource`], see [`crate::earthquake_event::UsgsDataSourceBuilder`].
+#[derive(Debug)]
+pub struct FdsnDataSourceBuilder {
+    client: Option<reqwest::Client>,
+    base_url: String,
+    timeout: Duration,
+    connect_timeout: Duration,
+    user_agent: String,
+    retry_policy: RetryPolicy,
+    format: Format,
+}
+
+impl FdsnDataSourceBuilder {
+    fn new(base_url: String) -> Self {
+        Self {
+            client: None,
+            base_url,
+            timeout: DEFAULT_TIMEOUT,
+            connect_timeout: DEFAULT_CONNECT_TIMEOUT,
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            retry_policy: RetryPolicy::default(),
+            format: Format::Text,
+        }
+    }
+
+    pub fn client(mut self, client: reqwest::Client) -> Self {
+        self.client = Some(client);
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn connect_timeout(mut self, connect_timeout: Duration) -> Self {
+        self.connect_timeout = connect_timeout;
+        self
+    }
+
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = user_agent.into();
+        self
+    }
+
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    // Format requested when the query asks for one the standard does not define
+    pub fn format(mut self, format: Format) -> Self {
+        self.format = format;
+        self
+    }
+
+    pub fn build(self) -> Result<FdsnDataSource, Errors> {
+        let format = match self.format {
+            format @ (Format::Xml | Format::Text) => format,
+            Format::GeoJson | Format::Csv => {
+                return Err(Errors::UnsupportedParameters(vec![format!(
+                    "format={}",
+                    self.format.as_str()
+                )]))
+            }
+        };
+
+        let base_url = parse_base_url(self.base_url)?;
+        let client = match self.client {
+            Some(client) => client,
+            None => build_client(self.user_agent, self.connect_timeout)?,
+        };
+
+        Ok(FdsnDataSource {
+            client,
+            base_url,
+            timeout: self.timeout,
+            retry_policy: self.retry_policy,
+            format,
+        })
+    }
+}
+
+/// What an FDSN event service supports, as described by its `application.wadl`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FdsnCapabilities {
+    // Resources such as `query`, `catalogs`, `contributors` and `version`
+    pub resources: BTreeSet<String>,
+    // Parameters of the `query` resource
+    pub parameters: BTreeSet<String>,
+    // Values listed for the `format` parameter; empty if the service does not enumerate them
+    pub formats: BTreeSet<String>,
+}
+
+impl FdsnCapabilities {
+    pub fn from_wadl(wadl: &str) -> Result<Self, Errors> {
+        let document = roxmltree::Document::parse(wadl)?;
+        let mut capabilities = Self::default();
+
+        for resource in document
+            .descendants()
+            .filter(|node| node.tag_name().name() == "resource")
+        {
+            let Some(path) = resource.attribute("path") else {
+                continue;
+            };
+            // The root resource, "/", only groups the others
+            let path = path.trim_matches('/');
+            if path.is_empty() {
+                continue;
+            }
+            capabilities.resources.insert(path.to_string());
+            if path != "query" {
+                continue;
+            }
+
+            for param in resource
+                .descendants()
+                .filter(|node| node.tag_name().name() == "param")
+            {
+                let Some(name) = param.attribute("name") else {
+                    continue;
+                };
+                capabilities.parameters.insert(name.to_string());
+                if name == "format" {
+                    capabilities.formats.extend(
+                        param
+                            .children()
+                            .filter(|node| node.tag_name().name() == "option")
+                            .filter_map(|option| option.attribute("value"))
+                            .map(str::to_string),
+                    );
+                }
+            }
+        }
+
+        Ok(capabilities)
+    }
+
+    pub fn supports(&self, parameter: &str) -> bool {
+        self.parameters.contains(parameter)
+    }
+
+    pub fn supports_format(&self, format: Format) -> bool {
+        self.formats.is_empty() || self.formats.contains(format.as_str())
+    }
+
+    /// Names of the parameters in `pairs` the service does not list, and the format if it is
+    /// not offered.
+    pub fn unsupported_parameters(&self, pairs: &[(&'static str, String)]) -> Vec<String> {
+        pairs
+            .iter()
+            .filter(|(name, value)| {
+                !self.supports(name)
+                    || (*name == "format"
+                        && !self.formats.is_empty()
+                        && !self.formats.contains(value))
+            })
+            .map(|(name, value)| match *name {
+                "format" => format!("format={value}"),
+                _ => name.to_string(),
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,248 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use futures::TryStreamExt;
+use reqwest::header::{HeaderName, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use reqwest::StatusCode;
+use tracing::{instrument, Span};
+
+use crate::earthquake_event::{
+    build_client, parse_base_url, successful, EarthquakeDataSource, EarthquakeEvent, Errors,
+    DEFAULT_CONNECT_TIMEOUT, DEFAULT_TIMEOUT, DEFAULT_USER_AGENT,
+};
+use crate::query::EventQuery;
+use crate::retry::RetryPolicy;
+use crate::stream::decode_geojson;
+
+/// Base URL of the USGS real-time GeoJSON summary feeds.
+pub const USGS_FEED_BASE_URL: &str = "https://earthquake.usgs.gov/earthquakes/feed/v1.0/summary/";
+
+// Minimum magnitude of a summary feed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeedMagnitude {
+    Significant,
+    M4_5,
+    M2_5,
+    M1_0,
+    All,
+}
+
+impl FeedMagnitude {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FeedMagnitude::Significant => "significant",
+            FeedMagnitude::M4_5 => "4.5",
+            FeedMagnitude::M2_5 => "2.5",
+            FeedMagnitude::M1_0 => "1.0",
+            FeedMagnitude::All => "all",
+        }
+    }
+}
+
+// Time span a summary feed covers, counted back from now
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeedPeriod {
+    Hour,
+    Day,
+    Week,
+    Month,
+}
+
+impl FeedPeriod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FeedPeriod::Hour => "hour",
+            FeedPeriod::Day => "day",
+            FeedPeriod::Week => "week",
+            FeedPeriod::Month => "month",
+        }
+    }
+}
+
+/// One of the USGS summary feeds, e.g. `2.5_day.geojson`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Feed {
+    pub magnitude: FeedMagnitude,
+    pub period: FeedPeriod,
+}
+
+impl Feed {
+    pub fn new(magnitude: FeedMagnitude, period: FeedPeriod) -> Self {
+        Self { magnitude, period }
+    }
+
+    pub fn file_name(&self) -> String {
+        format!(
+            "{}_{}.geojson",
+            self.magnitude.as_str(),
+            self.period.as_str()
+        )
+    }
+}
+
+// The last feed contents and the validators to revalidate them with
+#[derive(Debug, Default)]
+struct FeedState {
+    etag: Option<String>,
+    last_modified: Option<String>,
+    events: Vec<EarthquakeEvent>,
+}
+
+/// Reads a USGS summary feed, a cheap alternative to the query endpoint for polling.
+///
+/// Requests are conditional on the `ETag` and `Last-Modified` of the previous response, so an
+/// unchanged feed is answered with `304 Not Modified` and the events already held. Queries are
+/// answered in memory and can only see what the feed covers; clones share the feed state.
+#[derive(Debug, Clone)]
+pub struct FeedDataSource {
+    client: reqwest::Client,
+    url: reqwest::Url,
+    timeout: Duration,
+    retry_policy: RetryPolicy,
+    state: Arc<Mutex<FeedState>>,
+}
+
+impl FeedDataSource {
+    pub fn new(feed: Feed) -> Result<Self, Errors> {
+        Self::builder(feed).build()
+    }
+
+    pub fn builder(feed: Feed) -> FeedDataSourceBuilder {
+        FeedDataSourceBuilder::new(feed)
+    }
+
+    pub fn url(&self) -> &reqwest::Url {
+        &self.url
+    }
+
+    // Fetches the feed unless it is unchanged, returning the current events
+    async fn refresh(&self) -> Result<Vec<EarthquakeEvent>, Errors> {
+        let mut request = self.client.get(self.url.clone()).timeout(self.timeout);
+        {
+            let state = self.state.lock().unwrap();
+            if let Some(etag) = &state.etag {
+                request = request.header(IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = &state.last_modified {
+                request = request.header(IF_MODIFIED_SINCE, last_modified);
+            }
+        }
+        let request = request.build()?;
+        Span::current().record("url", request.url().as_str());
+
+        let response = self.retry_policy.execute(&self.client, request).await?;
+        if response.status() == StatusCode::NOT_MODIFIED {
+            Span::current().record("modified", false);
+            return Ok(self.state.lock().unwrap().events.clone());
+        }
+        let response = successful(response).await?;
+        Span::current().record("modified", true);
+
+        let etag = header_value(&response, ETAG);
+        let last_modified = header_value(&response, LAST_MODIFIED);
+
+        let events: Vec<EarthquakeEvent> = decode_geojson(response.bytes_stream())
+            .try_collect()
+            .await?;
+        *self.state.lock().unwrap() = FeedState {
+            etag,
+            last_modified,
+            events: events.clone(),
+        };
+        Ok(events)
+    }
+}
+
+fn header_value(response: &reqwest::Response, name: HeaderName) -> Option<String> {
+    let value = response.headers().get(name)?.to_str().ok()?;
+    Some(value.to_string())
+}
+
+#[async_trait]
+impl EarthquakeDataSource for FeedDataSource {
+    type Error = Errors;
+
+    #[instrument(skip(self), fields(url, attempts, modified))]
+    async fn fetch_earthquake_data(
+        &self,
+        query: &EventQuery,
+    ) -> Result<Vec<EarthquakeEvent>, Errors> {
+        let events = self.refresh().await?;
+        Ok(query.select(events))
+    }
+}
+
+/// Configures a [`FeedDataSource`], see [`crate::earthquake_event::UsgsDataSourceBuilder`].
+#[derive(Debug)]
+pub struct FeedDataSourceBuilder {
+    feed: Feed,
+    client: Option<reqwest::Client>,
+    base_url: String,
+    timeout: Duration,
+    connect_timeout: Duration,
+    user_agent: String,
+    retry_policy: RetryPolicy,
+}
+
+impl FeedDataSourceBuilder {
+    fn new(feed: Feed) -> Self {
+        Self {
+            feed,
+            client: None,
+            base_url: USGS_FEED_BASE_URL.to_string(),
+            timeout: DEFAULT_TIMEOUT,
+            connect_timeout: DEFAULT_CONNECT_TIMEOUT,
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            retry_policy: RetryPolicy::default(),
+        }
+    }
+
+    pub fn client(mut self, client: reqwest::Client) -> Self {
+        self.client = Some(client);
+        self
+    }
+
+    pub fn base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn connect_timeout(mut self, connect_timeout: Duration) -> Self {
+        self.connect_timeout = connect_timeout;
+        self
+    }
+
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = user_agent.into();
+        self
+    }
+
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    pub fn build(self) -> Result<FeedDataSource, Errors> {
+        let url = parse_base_url(self.base_url)?
+            .join(&self.feed.file_name())
+            .map_err(|error| Errors::InvalidBaseUrl(error.to_string()))?;
+        let client = match self.client {
+            Some(client) => client,
+            None => build_client(self.user_agent, self.connect_timeout)?,
+        };
+
+        Ok(FeedDataSource {
+            client,
+            url,
+            timeout: self.timeout,
+            retry_policy: self.retry_policy,
+            state: Arc::default(),
+        })
+    }
+}
@@ -1,6 +1,11 @@
+use chrono::{DateTime, Datelike, Duration, Months, NaiveDate, NaiveTime, Utc};
+use futures::{StreamExt, TryStreamExt};
 use tracing::instrument;
 
 use super::earthquake_event::*;
+use crate::cache::{CacheConfig, Cached};
+use crate::query::{EventQuery, OrderBy, QueryError, USGS_MAX_LIMIT};
+use crate::throttle::{Throttle, ThrottleConfig, Throttled};
 
 #[instrument]
 pub async fn run_fetch(
@@ -8,10 +13,207 @@ pub async fn run_fetch(
     end_time: &str,
     min_magnitude: i32,
 ) -> Result<Vec<EarthquakeEvent>, Errors> {
-    let usgs_data_source = UsgsDataSource;
-    let format = "geojson";
+    // Politeness settings come from EARTHQUAKE_MAX_IN_FLIGHT and EARTHQUAKE_REQUESTS_PER_SECOND
+    let throttle_config = ThrottleConfig::from_env();
+    let usgs_data_source =
+        Throttled::new(UsgsDataSource::default(), Throttle::new(&throttle_config));
+    let query = EventQuery::builder()
+        .time_range(start_time, end_time)?
+        .min_magnitude(min_magnitude as f64)
+        .build()?;
 
-    usgs_data_source
-        .fetch_earthquake_data(format, start_time, end_time, &min_magnitude.to_string())
-        .await
+    fetch_windowed(&usgs_data_source, &query, throttle_config.max_in_flight).await
+}
+
+/// Like [`run_fetch`], but answers from the on-disk cache described by `cache_config` where it
+/// can. In offline mode nothing is requested and windows missing from the cache fail with
+/// [`Errors::CacheMiss`].
+///
+/// Windows are whole calendar months, see [`fetch_calendar_windowed`], so runs on later days
+/// reuse the cached months.
+#[instrument]
+pub async fn run_fetch_cached(
+    start_time: &str,
+    end_time: &str,
+    min_magnitude: i32,
+    cache_config: CacheConfig,
+) -> Result<Vec<EarthquakeEvent>, Errors> {
+    let throttle_config = ThrottleConfig::from_env();
+    let usgs_data_source = Cached::new(
+        Throttled::new(UsgsDataSource::default(), Throttle::new(&throttle_config)),
+        cache_config,
+    );
+    let query = EventQuery::builder()
+        .time_range(start_time, end_time)?
+        .min_magnitude(min_magnitude as f64)
+        .build()?;
+
+    fetch_calendar_windowed(&usgs_data_source, &query, throttle_config.max_in_flight).await
+}
+
+/// Fetches all events matching `query`, splitting its time range into windows that each stay
+/// under the USGS result cap. Windows are fetched with at most `max_concurrency` requests in
+/// flight and the merged events are returned in time order.
+///
+/// The windows are requested without the query's `orderby`, `limit` and `offset`; those apply
+/// once, to the merged events.
+#[instrument(skip(source))]
+pub async fn fetch_windowed<S>(
+    source: &S,
+    query: &EventQuery,
+    max_concurrency: usize,
+) -> Result<Vec<EarthquakeEvent>, Errors>
+where
+    S: CountableDataSource<Error = Errors> + Sync,
+{
+    time_range(query)?;
+    let earthquake_events =
+        fetch_windows(source, vec![query.without_paging()], max_concurrency).await?;
+
+    Ok(query.arrange(earthquake_events, OrderBy::TimeAsc))
+}
+
+/// Like [`fetch_windowed`], but starts from the calendar months (UTC) the query's time range
+/// touches rather than from the range itself, and drops the events outside the range at the end.
+///
+/// The windows then depend only on the months covered and not on the exact range, so the
+/// cache keys of [`Cached`] stay the same from one day to the next and closed months, once
+/// settled, are never fetched again. At most a month on either side is fetched in excess.
+#[instrument(skip(source))]
+pub async fn fetch_calendar_windowed<S>(
+    source: &S,
+    query: &EventQuery,
+    max_concurrency: usize,
+) -> Result<Vec<EarthquakeEvent>, Errors>
+where
+    S: CountableDataSource<Error = Errors> + Sync,
+{
+    let (start_time, end_time) = time_range(query)?;
+    let mut earthquake_events = fetch_windows(
+        source,
+        calendar_months(&query.without_paging(), start_time, end_time),
+        max_concurrency,
+    )
+    .await?;
+    earthquake_events.retain(|event| event.time >= start_time && event.time <= end_time);
+
+    Ok(query.arrange(earthquake_events, OrderBy::TimeAsc))
+}
+
+fn time_range(query: &EventQuery) -> Result<(DateTime<Utc>, DateTime<Utc>), Errors> {
+    let start_time = query
+        .start_time()
+        .ok_or(QueryError::MissingParameter("starttime"))?;
+    let end_time = query
+        .end_time()
+        .ok_or(QueryError::MissingParameter("endtime"))?;
+    Ok((start_time, end_time))
+}
+
+// Closed windows from the first millisecond of each month to the last
+fn calendar_months(
+    query: &EventQuery,
+    start_time: DateTime<Utc>,
+    end_time: DateTime<Utc>,
+) -> Vec<EventQuery> {
+    let mut windows = Vec::new();
+    let mut month = NaiveDate::from_ymd_opt(start_time.year(), start_time.month(), 1);
+    while let Some(first_day) = month {
+        let window_start = first_day.and_time(NaiveTime::MIN).and_utc();
+        if window_start > end_time {
+            break;
+        }
+        month = first_day.checked_add_months(Months::new(1));
+        let window_end = match month {
+            Some(next) => next.and_time(NaiveTime::MIN).and_utc() - Duration::milliseconds(1),
+            None => end_time,
+        };
+        windows.push(query.with_time_range(window_start, window_end));
+    }
+    windows
+}
+
+async fn fetch_windows<S>(
+    source: &S,
+    initial_windows: Vec<EventQuery>,
+    max_concurrency: usize,
+) -> Result<Vec<EarthquakeEvent>, Errors>
+where
+    S: CountableDataSource<Error = Errors> + Sync,
+{
+    let windows = plan_windows(source, initial_windows, max_concurrency).await?;
+    tracing::info!(windows = windows.len(), "Fetching windows");
+
+    let mut earthquake_events: Vec<EarthquakeEvent> = futures::stream::iter(windows)
+        .map(|window| async move {
+            // The window may have emptied since it was counted
+            match source.fetch_earthquake_data(&window).await {
+                Err(Errors::NoData) => Ok(Vec::new()),
+                result => result,
+            }
+        })
+        .buffered(max_concurrency.max(1))
+        .try_concat()
+        .await?;
+    earthquake_events.sort_by_key(|event| event.time);
+
+    Ok(earthquake_events)
+}
+
+// Recursively halves the windows until every one matches at most USGS_MAX_LIMIT events.
+// Windows without any events are dropped and the rest are returned in time order.
+async fn plan_windows<S>(
+    source: &S,
+    initial_windows: Vec<EventQuery>,
+    max_concurrency: usize,
+) -> Result<Vec<EventQuery>, Errors>
+where
+    S: CountableDataSource<Error = Errors> + Sync,
+{
+    let mut windows = Vec::new();
+    let mut pending = initial_windows;
+
+    while !pending.is_empty() {
+        let counts: Vec<(EventQuery, u64)> = futures::stream::iter(pending)
+            .map(|window| async move {
+                let count = source.count_events(&window).await?;
+                Ok::<_, Errors>((window, count))
+            })
+            .buffered(max_concurrency.max(1))
+            .try_collect()
+            .await?;
+
+        pending = Vec::new();
+        for (window, count) in counts {
+            if count == 0 {
+                continue;
+            }
+            if count <= USGS_MAX_LIMIT as u64 {
+                windows.push(window);
+                continue;
+            }
+
+            let (start_time, end_time) = window
+                .start_time()
+                .zip(window.end_time())
+                .expect("windows always carry a time range");
+            // Both halves are closed intervals, so the second one starts a millisecond later
+            let middle =
+                start_time + Duration::milliseconds((end_time - start_time).num_milliseconds() / 2);
+            let next = middle + Duration::milliseconds(1);
+            if next > end_time {
+                return Err(Errors::WindowTooDense {
+                    count,
+                    start_time,
+                    end_time,
+                });
+            }
+            tracing::debug!(count, %start_time, %end_time, "Splitting window");
+            pending.push(window.with_time_range(start_time, middle));
+            pending.push(window.with_time_range(next, end_time));
+        }
+    }
+
+    windows.sort_by_key(|window| window.start_time());
+    Ok(windows)
 }
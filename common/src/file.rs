@@ -0,0 +1,127 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use tokio::sync::OnceCell;
+use tracing::instrument;
+
+use crate::earthquake_event::{
+    parse_events, CountableDataSource, EarthquakeDataSource, EarthquakeEvent, Errors,
+};
+use crate::query::{EventQuery, Format};
+
+/// Reads events from a local catalog file, or from every catalog file in a directory.
+///
+/// The format of each file is taken from its extension unless one is given with
+/// [`FileDataSource::format`]. Queries are answered in memory with the same filters the web
+/// service applies; an empty result is not an error.
+///
+/// The files are read once, on the first query, and kept in memory for the queries after it,
+/// including those of clones. Files in a directory that fail to read are skipped with a warning.
+#[derive(Debug, Clone)]
+pub struct FileDataSource {
+    path: PathBuf,
+    format: Option<Format>,
+    events: Arc<OnceCell<Vec<EarthquakeEvent>>>,
+}
+
+impl FileDataSource {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            format: None,
+            events: Arc::default(),
+        }
+    }
+
+    // Reads every file as `format` regardless of its extension
+    pub fn format(mut self, format: Format) -> Self {
+        self.format = Some(format);
+        self.events = Arc::default();
+        self
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    async fn events(&self) -> Result<&[EarthquakeEvent], Errors> {
+        let events = self.events.get_or_try_init(|| self.read_all()).await?;
+        Ok(events)
+    }
+
+    async fn read_all(&self) -> Result<Vec<EarthquakeEvent>, Errors> {
+        if !tokio::fs::metadata(&self.path).await?.is_dir() {
+            let format = self
+                .format_of(&self.path)
+                .ok_or_else(|| Errors::UnknownFormat(self.path.clone()))?;
+            return read_file(&self.path, format).await;
+        }
+
+        // Files in a directory are read in name order; ones of unknown format are skipped
+        let mut paths = Vec::new();
+        let mut entries = tokio::fs::read_dir(&self.path).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            if entry.file_type().await?.is_file() {
+                paths.push(entry.path());
+            }
+        }
+        paths.sort();
+
+        let mut events = Vec::new();
+        for path in paths {
+            let Some(format) = self.format_of(&path) else {
+                tracing::debug!(path = %path.display(), "Skipping file of unknown format");
+                continue;
+            };
+            match read_file(&path, format).await {
+                Ok(file_events) => events.extend(file_events),
+                Err(error) => tracing::warn!(%error, "Skipping unreadable file"),
+            }
+        }
+        Ok(events)
+    }
+
+    fn format_of(&self, path: &Path) -> Option<Format> {
+        self.format.or_else(|| {
+            path.extension()
+                .and_then(|extension| extension.to_str())
+                .and_then(Format::from_extension)
+        })
+    }
+}
+
+// Errors name the file they came from
+async fn read_file(path: &Path, format: Format) -> Result<Vec<EarthquakeEvent>, Errors> {
+    tracing::info!(path = %path.display(), "Reading");
+    let events = match tokio::fs::read_to_string(path).await {
+        Ok(contents) => parse_events(format, &contents),
+        Err(error) => Err(error.into()),
+    };
+    events.map_err(|source| Errors::File {
+        path: path.to_path_buf(),
+        source: Box::new(source),
+    })
+}
+
+#[async_trait]
+impl EarthquakeDataSource for FileDataSource {
+    type Error = Errors;
+
+    #[instrument(skip(self), fields(path = %self.path.display()))]
+    async fn fetch_earthquake_data(
+        &self,
+        query: &EventQuery,
+    ) -> Result<Vec<EarthquakeEvent>, Errors> {
+        Ok(query.select(self.events().await?.to_vec()))
+    }
+}
+
+#[async_trait]
+impl CountableDataSource for FileDataSource {
+    #[instrument(skip(self), fields(path = %self.path.display()))]
+    async fn count_events(&self, query: &EventQuery) -> Result<u64, Errors> {
+        let events = self.events().await?;
+        Ok(events.iter().filter(|event| query.matches(event)).count() as u64)
+    }
+}
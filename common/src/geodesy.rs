@@ -0,0 +1,275 @@
+use crate::coordinates::Coordinates;
+
+/// Mean Earth radius used by the spherical formulas.
+pub const EARTH_RADIUS_KM: f64 = 6371.0;
+
+// WGS 84 ellipsoid, for Vincenty's formulae
+const WGS84_SEMI_MAJOR_AXIS_KM: f64 = 6378.137;
+const WGS84_FLATTENING: f64 = 1.0 / 298.257_223_563;
+
+const VINCENTY_MAX_ITERATIONS: usize = 200;
+const VINCENTY_TOLERANCE: f64 = 1e-12;
+
+/// A latitude/longitude rectangle.
+///
+/// Boxes crossing the antimeridian have `max_lon` past 180, e.g. 170 to 190, the same
+/// convention as [`crate::query::Region::Rectangle`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingBox {
+    pub min_lat: f64,
+    pub max_lat: f64,
+    pub min_lon: f64,
+    pub max_lon: f64,
+}
+
+impl BoundingBox {
+    /// The smallest box enclosing all `points`, or `None` if there are none.
+    ///
+    /// The box leaves out the widest gap between longitudes, so points on both sides of the
+    /// antimeridian give a box crossing it rather than one spanning the globe.
+    pub fn enclosing<'a>(points: impl IntoIterator<Item = &'a Coordinates<f64>>) -> Option<Self> {
+        let mut min_lat = f64::INFINITY;
+        let mut max_lat = f64::NEG_INFINITY;
+        let mut longitudes = Vec::new();
+        for point in points {
+            min_lat = min_lat.min(point.lat);
+            max_lat = max_lat.max(point.lat);
+            longitudes.push(point.lon);
+        }
+        longitudes.sort_by(f64::total_cmp);
+
+        let (&first, &last) = (longitudes.first()?, longitudes.last()?);
+        // Widest gap, starting with the one across the antimeridian
+        let (mut min_lon, mut max_lon) = (first, last);
+        let mut widest = first + 360.0 - last;
+        for pair in longitudes.windows(2) {
+            if pair[1] - pair[0] > widest {
+                widest = pair[1] - pair[0];
+                (min_lon, max_lon) = (pair[1], pair[0] + 360.0);
+            }
+        }
+
+        Some(BoundingBox {
+            min_lat,
+            max_lat,
+            min_lon,
+            max_lon,
+        })
+    }
+
+    pub fn crosses_antimeridian(&self) -> bool {
+        self.max_lon > 180.0
+    }
+
+    pub fn contains(&self, coordinates: &Coordinates<f64>) -> bool {
+        let longitudes = self.min_lon..=self.max_lon;
+        let lon = coordinates.lon;
+        (self.min_lat..=self.max_lat).contains(&coordinates.lat)
+            && [lon, lon - 360.0, lon + 360.0]
+                .iter()
+                .any(|lon| longitudes.contains(lon))
+    }
+}
+
+// Geodesy on latitude and longitude; depth is carried along but never enters a distance
+impl Coordinates<f64> {
+    /// Great-circle distance in kilometers on a spherical Earth.
+    pub fn haversine_km(&self, other: &Coordinates<f64>) -> f64 {
+        let (lat1, lat2) = (self.lat.to_radians(), other.lat.to_radians());
+        let half_dlat = (lat2 - lat1) / 2.0;
+        let half_dlon = (other.lon - self.lon).to_radians() / 2.0;
+        let a = half_dlat.sin().powi(2) + lat1.cos() * lat2.cos() * half_dlon.sin().powi(2);
+        2.0 * EARTH_RADIUS_KM * a.sqrt().min(1.0).asin()
+    }
+
+    /// Distance in kilometers on the WGS 84 ellipsoid by Vincenty's inverse formula.
+    ///
+    /// Accurate to well under a meter, but `None` for nearly antipodal points, where the
+    /// iteration does not converge; fall back to [`Coordinates::haversine_km`] there.
+    pub fn vincenty_km(&self, other: &Coordinates<f64>) -> Option<f64> {
+        let a = WGS84_SEMI_MAJOR_AXIS_KM;
+        let f = WGS84_FLATTENING;
+        let b = a * (1.0 - f);
+
+        let l = (other.lon - self.lon).to_radians();
+        let u1 = ((1.0 - f) * self.lat.to_radians().tan()).atan();
+        let u2 = ((1.0 - f) * other.lat.to_radians().tan()).atan();
+        let (sin_u1, cos_u1) = u1.sin_cos();
+        let (sin_u2, cos_u2) = u2.sin_cos();
+
+        let mut lambda = l;
+        for _ in 0..VINCENTY_MAX_ITERATIONS {
+            let (sin_lambda, cos_lambda) = lambda.sin_cos();
+            let sin_sigma = ((cos_u2 * sin_lambda).powi(2)
+                + (cos_u1 * sin_u2 - sin_u1 * cos_u2 * cos_lambda).powi(2))
+            .sqrt();
+            if sin_sigma == 0.0 {
+                // Coincident points
+                return Some(0.0);
+            }
+            let cos_sigma = sin_u1 * sin_u2 + cos_u1 * cos_u2 * cos_lambda;
+            let sigma = sin_sigma.atan2(cos_sigma);
+            let sin_alpha = cos_u1 * cos_u2 * sin_lambda / sin_sigma;
+            let cos_sq_alpha = 1.0 - sin_alpha * sin_alpha;
+            // Zero on the equator
+            let cos_2sigma_m = if cos_sq_alpha == 0.0 {
+                0.0
+            } else {
+                cos_sigma - 2.0 * sin_u1 * sin_u2 / cos_sq_alpha
+            };
+            let c = f / 16.0 * cos_sq_alpha * (4.0 + f * (4.0 - 3.0 * cos_sq_alpha));
+
+            let previous = lambda;
+            lambda = l
+                + (1.0 - c)
+                    * f
+                    * sin_alpha
+                    * (sigma
+                        + c * sin_sigma
+                            * (cos_2sigma_m + c * cos_sigma * (-1.0 + 2.0 * cos_2sigma_m.powi(2))));
+            if (lambda - previous).abs() > VINCENTY_TOLERANCE {
+                continue;
+            }
+
+            let u_sq = cos_sq_alpha * (a * a - b * b) / (b * b);
+            let big_a =
+                1.0 + u_sq / 16384.0 * (4096.0 + u_sq * (-768.0 + u_sq * (320.0 - 175.0 * u_sq)));
+            let big_b = u_sq / 1024.0 * (256.0 + u_sq * (-128.0 + u_sq * (74.0 - 47.0 * u_sq)));
+            let delta_sigma = big_b
+                * sin_sigma
+                * (cos_2sigma_m
+                    + big_b / 4.0
+                        * (cos_sigma * (-1.0 + 2.0 * cos_2sigma_m.powi(2))
+                            - big_b / 6.0
+                                * cos_2sigma_m
+                                * (-3.0 + 4.0 * sin_sigma.powi(2))
+                                * (-3.0 + 4.0 * cos_2sigma_m.powi(2))));
+            return Some(b * big_a * (sigma - delta_sigma));
+        }
+
+        None
+    }
+
+    /// Initial bearing towards `other` along the great circle, in degrees clockwise from north
+    /// in `[0, 360)`.
+    pub fn initial_bearing(&self, other: &Coordinates<f64>) -> f64 {
+        let (lat1, lat2) = (self.lat.to_radians(), other.lat.to_radians());
+        let dlon = (other.lon - self.lon).to_radians();
+        let y = dlon.sin() * lat2.cos();
+        let x = lat1.cos() * lat2.sin() - lat1.sin() * lat2.cos() * dlon.cos();
+        y.atan2(x).to_degrees().rem_euclid(360.0)
+    }
+
+    /// The point `distance_km` away along the great circle leaving at `bearing` degrees, at the
+    /// same depth.
+    pub fn destination(&self, bearing: f64, distance_km: f64) -> Coordinates<f64> {
+        let lat1 = self.lat.to_radians();
+        let bearing = bearing.to_radians();
+        let angle = distance_km / EARTH_RADIUS_KM;
+
+        let lat2 = (lat1.sin() * angle.cos() + lat1.cos() * angle.sin() * bearing.cos()).asin();
+        let dlon =
+            (bearing.sin() * angle.sin() * lat1.cos()).atan2(angle.cos() - lat1.sin() * lat2.sin());
+
+        Coordinates {
+            lat: lat2.to_degrees(),
+            lon: normalize_longitude(self.lon + dlon.to_degrees()),
+            depth: self.depth,
+        }
+    }
+
+    /// The point halfway along the great circle to `other`, at the mean depth.
+    pub fn midpoint(&self, other: &Coordinates<f64>) -> Coordinates<f64> {
+        let (lat1, lat2) = (self.lat.to_radians(), other.lat.to_radians());
+        let dlon = (other.lon - self.lon).to_radians();
+        let bx = lat2.cos() * dlon.cos();
+        let by = lat2.cos() * dlon.sin();
+
+        let lat = (lat1.sin() + lat2.sin()).atan2(((lat1.cos() + bx).powi(2) + by * by).sqrt());
+        let dlon = by.atan2(lat1.cos() + bx);
+
+        Coordinates {
+            lat: lat.to_degrees(),
+            lon: normalize_longitude(self.lon + dlon.to_degrees()),
+            depth: (self.depth + other.depth) / 2.0,
+        }
+    }
+
+    /// The smallest box holding every point within `radius_km`.
+    ///
+    /// Circles reaching a pole get the full longitude range; those reaching over the
+    /// antimeridian get a box crossing it.
+    pub fn bounding_box(&self, radius_km: f64) -> BoundingBox {
+        let angle = (radius_km / EARTH_RADIUS_KM).to_degrees();
+        let min_lat = self.lat - angle;
+        let max_lat = self.lat + angle;
+        if min_lat <= -90.0 || max_lat >= 90.0 {
+            return BoundingBox {
+                min_lat: min_lat.max(-90.0),
+                max_lat: max_lat.min(90.0),
+                min_lon: -180.0,
+                max_lon: 180.0,
+            };
+        }
+
+        let half_width = ((radius_km / EARTH_RADIUS_KM).sin() / self.lat.to_radians().cos())
+            .asin()
+            .to_degrees();
+        let mut min_lon = self.lon - half_width;
+        let mut max_lon = self.lon + half_width;
+        if min_lon < -180.0 {
+            min_lon += 360.0;
+            max_lon += 360.0;
+        }
+
+        BoundingBox {
+            min_lat,
+            max_lat,
+            min_lon,
+            max_lon,
+        }
+    }
+
+    /// Whether the point lies in a GeoJSON polygon: an exterior ring followed by any holes.
+    ///
+    /// Rings are taken as planar in longitude and latitude, so polygons crossing the
+    /// antimeridian must be split along it, as RFC 7946 asks of GeoJSON anyway.
+    pub fn in_polygon(&self, rings: &[Vec<Coordinates<f64>>]) -> bool {
+        match rings.split_first() {
+            Some((exterior, holes)) => {
+                self.in_ring(exterior) && !holes.iter().any(|hole| self.in_ring(hole))
+            }
+            None => false,
+        }
+    }
+
+    /// Whether the point lies in any polygon of a GeoJSON multi-polygon.
+    pub fn in_multi_polygon(&self, polygons: &[Vec<Vec<Coordinates<f64>>>]) -> bool {
+        polygons.iter().any(|rings| self.in_polygon(rings))
+    }
+
+    // Even-odd ray casting towards increasing longitude
+    fn in_ring(&self, ring: &[Coordinates<f64>]) -> bool {
+        let mut inside = false;
+        let Some(mut previous) = ring.last() else {
+            return false;
+        };
+        for vertex in ring {
+            if (vertex.lat > self.lat) != (previous.lat > self.lat) {
+                let crossing = vertex.lon
+                    + (self.lat - vertex.lat) * (previous.lon - vertex.lon)
+                        / (previous.lat - vertex.lat);
+                if self.lon < crossing {
+                    inside = !inside;
+                }
+            }
+            previous = vertex;
+        }
+        inside
+    }
+}
+
+// Longitude in [-180, 180)
+fn normalize_longitude(lon: f64) -> f64 {
+    (lon + 180.0).rem_euclid(360.0) - 180.0
+}
@@ -0,0 +1,241 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::earthquake_event::EarthquakeEvent;
+use crate::types::MagnitudeType;
+
+/// A conversion from some magnitude scale to moment magnitude.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Relation {
+    // The scale already is, or is taken as, moment magnitude
+    Identity,
+    // Mw = slope * M + intercept
+    Linear { slope: f64, intercept: f64 },
+    // Mw = a * M^2 + b * M + c
+    Quadratic { a: f64, b: f64, c: f64 },
+}
+
+impl Relation {
+    pub fn apply(&self, magnitude: f64) -> f64 {
+        match *self {
+            Relation::Identity => magnitude,
+            Relation::Linear { slope, intercept } => slope * magnitude + intercept,
+            Relation::Quadratic { a, b, c } => a * magnitude * magnitude + b * magnitude + c,
+        }
+    }
+}
+
+/// Converts one magnitude type, optionally only for one network, within a magnitude range.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConversionRule {
+    pub mag_type: MagnitudeType,
+    // Network code the rule is restricted to; rules without one apply to every network
+    #[serde(default)]
+    pub network: Option<String>,
+    pub relation: Relation,
+    // Inclusive range of the original magnitude the relation was derived for
+    #[serde(default)]
+    pub min_magnitude: Option<f64>,
+    #[serde(default)]
+    pub max_magnitude: Option<f64>,
+    // Publication the relation comes from
+    #[serde(default)]
+    pub reference: String,
+}
+
+impl ConversionRule {
+    pub fn new(mag_type: MagnitudeType, relation: Relation) -> Self {
+        Self {
+            mag_type,
+            network: None,
+            relation,
+            min_magnitude: None,
+            max_magnitude: None,
+            reference: String::new(),
+        }
+    }
+
+    pub fn network(mut self, network: impl Into<String>) -> Self {
+        self.network = Some(network.into());
+        self
+    }
+
+    pub fn range(mut self, min_magnitude: f64, max_magnitude: f64) -> Self {
+        self.min_magnitude = Some(min_magnitude);
+        self.max_magnitude = Some(max_magnitude);
+        self
+    }
+
+    pub fn reference(mut self, reference: impl Into<String>) -> Self {
+        self.reference = reference.into();
+        self
+    }
+
+    fn applies_to(&self, event: &EarthquakeEvent) -> bool {
+        event.mag_type.as_ref() == Some(&self.mag_type)
+            && self.network.as_ref().map_or(true, |network| {
+                event
+                    .net
+                    .as_deref()
+                    .is_some_and(|net| net.eq_ignore_ascii_case(network))
+            })
+            && self.min_magnitude.map_or(true, |min| event.mag >= min)
+            && self.max_magnitude.map_or(true, |max| event.mag <= max)
+    }
+}
+
+/// How many events [`Homogenizer::homogenize`] gave a proxy moment magnitude.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HomogenizationReport {
+    pub converted: usize,
+    // Events left without a proxy, by magnitude type; those without one under an empty name
+    pub unconverted: BTreeMap<String, usize>,
+}
+
+impl HomogenizationReport {
+    pub fn unconverted_count(&self) -> usize {
+        self.unconverted.values().sum()
+    }
+}
+
+/// Computes a proxy moment magnitude for events reported on other scales.
+///
+/// For each event the first applicable network-specific rule is used, else the first
+/// applicable general rule. Events no rule covers, including those without a magnitude, get no
+/// proxy.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Homogenizer {
+    pub rules: Vec<ConversionRule>,
+}
+
+impl Default for Homogenizer {
+    fn default() -> Self {
+        let moment_magnitudes = [
+            MagnitudeType::Mw,
+            MagnitudeType::Mww,
+            MagnitudeType::Mwc,
+            MagnitudeType::Mwb,
+            MagnitudeType::Mwr,
+        ]
+        .into_iter()
+        .map(|mag_type| ConversionRule::new(mag_type, Relation::Identity));
+
+        let mut rules: Vec<ConversionRule> = moment_magnitudes.collect();
+        rules.extend([
+            ConversionRule::new(
+                MagnitudeType::Mb,
+                Relation::Linear {
+                    slope: 0.85,
+                    intercept: 1.03,
+                },
+            )
+            .range(3.5, 6.2)
+            .reference("Scordilis (2006), J. Seismol. 10, 225-236"),
+            ConversionRule::new(
+                MagnitudeType::MbLg,
+                Relation::Quadratic {
+                    a: 0.0933,
+                    b: 0.24,
+                    c: 1.14,
+                },
+            )
+            .range(3.0, 6.5)
+            .reference("Johnston (1996), Geophys. J. Int. 124, 381-414"),
+            ConversionRule::new(
+                MagnitudeType::Ml,
+                Relation::Quadratic {
+                    a: 0.0376,
+                    b: 0.646,
+                    c: 0.53,
+                },
+            )
+            .range(1.0, 6.0)
+            .reference("Grünthal, Wahlström & Stromeyer (2009), J. Seismol. 13, 517-541"),
+            // Duration magnitudes are calibrated against ML, so the ML relation carries over
+            ConversionRule::new(
+                MagnitudeType::Md,
+                Relation::Quadratic {
+                    a: 0.0376,
+                    b: 0.646,
+                    c: 0.53,
+                },
+            )
+            .range(1.0, 6.0)
+            .reference(
+                "Eaton (1992), Bull. Seismol. Soc. Am. 82, 533-579; \
+                 Grünthal, Wahlström & Stromeyer (2009), J. Seismol. 13, 517-541",
+            ),
+        ]);
+
+        // Ms_20 is the IASPEI standard surface-wave magnitude the Ms relations were derived for
+        for mag_type in [MagnitudeType::Ms, MagnitudeType::Ms20] {
+            rules.extend([
+                ConversionRule::new(
+                    mag_type.clone(),
+                    Relation::Linear {
+                        slope: 0.67,
+                        intercept: 2.07,
+                    },
+                )
+                .range(3.0, 6.1)
+                .reference("Scordilis (2006), J. Seismol. 10, 225-236"),
+                ConversionRule::new(
+                    mag_type,
+                    Relation::Linear {
+                        slope: 0.99,
+                        intercept: 0.08,
+                    },
+                )
+                .range(6.2, 8.2)
+                .reference("Scordilis (2006), J. Seismol. 10, 225-236"),
+            ]);
+        }
+
+        Self { rules }
+    }
+}
+
+impl Homogenizer {
+    pub fn new(rules: Vec<ConversionRule>) -> Self {
+        Self { rules }
+    }
+
+    pub fn proxy_mw(&self, event: &EarthquakeEvent) -> Option<f64> {
+        if !event.mag.is_finite() {
+            return None;
+        }
+
+        let applicable = || self.rules.iter().filter(|rule| rule.applies_to(event));
+        let rule = applicable()
+            .find(|rule| rule.network.is_some())
+            .or_else(|| applicable().next())?;
+        Some(rule.relation.apply(event.mag))
+    }
+
+    /// Sets `mw_proxy` on every event, leaving the original magnitude as it is.
+    ///
+    /// Events no rule covers get `None`; the report counts them by magnitude type.
+    pub fn homogenize(&self, events: &mut [EarthquakeEvent]) -> HomogenizationReport {
+        let mut report = HomogenizationReport::default();
+        for event in events {
+            event.mw_proxy = self.proxy_mw(event);
+            match event.mw_proxy {
+                Some(_) => report.converted += 1,
+                None => {
+                    *report
+                        .unconverted
+                        .entry(
+                            event
+                                .mag_type
+                                .as_ref()
+                                .map_or_else(String::new, MagnitudeType::to_string),
+                        )
+                        .or_default() += 1
+                }
+            }
+        }
+        report
+    }
+}
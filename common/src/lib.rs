@@ -1,5 +1,23 @@
 #[cfg(feature = "blocking")]
 pub mod blocking;
+pub mod cache;
+pub mod coordinates;
+pub mod delimited;
+pub mod detail;
 pub mod earthquake_event;
+pub mod fdsn;
+pub mod feed;
 pub mod fetch;
+pub mod file;
+pub mod geodesy;
+pub mod homogenize;
+pub mod merge;
+pub mod place;
+pub mod quakeml;
+pub mod query;
+pub mod regionalization;
+pub mod retry;
+pub mod stream;
+pub mod throttle;
+pub mod types;
 pub mod utils;
@@ -0,0 +1,135 @@
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use crate::earthquake_event::EarthquakeEvent;
+
+// Catalog name of events without a network code
+const UNKNOWN_CATALOG: &str = "unknown";
+
+/// How events from different catalogs are associated and which solution is preferred.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MergeConfig {
+    pub time_tolerance: Duration,
+    pub distance_tolerance_km: f64,
+    // Catalogs (network codes) from most to least preferred; unlisted ones rank after all listed
+    pub agency_priority: Vec<String>,
+}
+
+impl Default for MergeConfig {
+    fn default() -> Self {
+        Self {
+            time_tolerance: Duration::from_secs(16),
+            distance_tolerance_km: 100.0,
+            agency_priority: Vec::new(),
+        }
+    }
+}
+
+impl MergeConfig {
+    fn rank(&self, catalog: &str) -> usize {
+        self.agency_priority
+            .iter()
+            .position(|agency| agency.eq_ignore_ascii_case(catalog))
+            .unwrap_or(self.agency_priority.len())
+    }
+}
+
+/// One physical event: the preferred solution and those other catalogs reported for it.
+#[derive(Debug, Clone)]
+pub struct MergedEvent {
+    pub preferred: EarthquakeEvent,
+    pub alternates: Vec<EarthquakeEvent>,
+}
+
+impl MergedEvent {
+    pub fn solutions(&self) -> impl Iterator<Item = &EarthquakeEvent> {
+        std::iter::once(&self.preferred).chain(&self.alternates)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct MergeReport {
+    // In time order of the earliest solution
+    pub events: Vec<MergedEvent>,
+    // Ids of the events no other catalog reported, by catalog
+    pub unmatched: BTreeMap<String, Vec<String>>,
+}
+
+impl MergeReport {
+    pub fn preferred(&self) -> impl Iterator<Item = &EarthquakeEvent> {
+        self.events.iter().map(|event| &event.preferred)
+    }
+}
+
+/// The catalog an event came from, taken from its network code.
+pub fn catalog_of(event: &EarthquakeEvent) -> &str {
+    event.net.as_deref().unwrap_or(UNKNOWN_CATALOG)
+}
+
+/// Associates the solutions different catalogs give for the same event.
+///
+/// Events are taken in time order. Each joins the group whose earliest solution is within both
+/// tolerances and closest to it, provided the group has no solution from the same catalog yet;
+/// otherwise it starts a group of its own. The preferred solution of a group is the one from
+/// the highest-priority catalog, and the most recently updated among equals.
+pub fn merge_events(events: Vec<EarthquakeEvent>, config: &MergeConfig) -> MergeReport {
+    let mut events = events;
+    events.sort_by_key(|event| event.time);
+
+    let tolerance_ms = (config.time_tolerance.as_millis() as i64).max(1);
+    let tolerance_km = config.distance_tolerance_km.max(f64::EPSILON);
+
+    let mut groups: Vec<Vec<EarthquakeEvent>> = Vec::new();
+    // Groups before `open` started too long ago to take more events
+    let mut open = 0;
+
+    for event in events {
+        while open < groups.len()
+            && (event.time - groups[open][0].time).num_milliseconds() > tolerance_ms
+        {
+            open += 1;
+        }
+
+        let catalog = catalog_of(&event);
+        let best = groups[open..]
+            .iter()
+            .enumerate()
+            .filter(|(_, group)| group.iter().all(|member| catalog_of(member) != catalog))
+            .filter_map(|(index, group)| {
+                let anchor = &group[0];
+                let distance = anchor.coordinates.haversine_km(&event.coordinates);
+                let score = (event.time - anchor.time).num_milliseconds() as f64
+                    / tolerance_ms as f64
+                    + distance / tolerance_km;
+                (distance <= config.distance_tolerance_km).then_some((open + index, score))
+            })
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(index, _)| index);
+
+        match best {
+            Some(index) => groups[index].push(event),
+            None => groups.push(vec![event]),
+        }
+    }
+
+    let mut report = MergeReport::default();
+    for mut group in groups {
+        if let [event] = group.as_slice() {
+            report
+                .unmatched
+                .entry(catalog_of(event).to_string())
+                .or_default()
+                .push(event.id.clone());
+        }
+
+        group.sort_by_key(|event| (config.rank(catalog_of(event)), Reverse(event.updated)));
+        let preferred = group.remove(0);
+        report.events.push(MergedEvent {
+            preferred,
+            alternates: group,
+        });
+    }
+
+    report
+}
@@ -0,0 +1,127 @@
+use serde::{Deserialize, Serialize};
+
+/// One of the 16 compass points USGS uses in place descriptions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum CompassDirection {
+    N,
+    Nne,
+    Ne,
+    Ene,
+    E,
+    Ese,
+    Se,
+    Sse,
+    S,
+    Ssw,
+    Sw,
+    Wsw,
+    W,
+    Wnw,
+    Nw,
+    Nnw,
+}
+
+// In clockwise order from north, 22.5 degrees apart
+const COMPASS_POINTS: [(CompassDirection, &str); 16] = [
+    (CompassDirection::N, "N"),
+    (CompassDirection::Nne, "NNE"),
+    (CompassDirection::Ne, "NE"),
+    (CompassDirection::Ene, "ENE"),
+    (CompassDirection::E, "E"),
+    (CompassDirection::Ese, "ESE"),
+    (CompassDirection::Se, "SE"),
+    (CompassDirection::Sse, "SSE"),
+    (CompassDirection::S, "S"),
+    (CompassDirection::Ssw, "SSW"),
+    (CompassDirection::Sw, "SW"),
+    (CompassDirection::Wsw, "WSW"),
+    (CompassDirection::W, "W"),
+    (CompassDirection::Wnw, "WNW"),
+    (CompassDirection::Nw, "NW"),
+    (CompassDirection::Nnw, "NNW"),
+];
+
+impl CompassDirection {
+    pub fn parse(direction: &str) -> Option<Self> {
+        COMPASS_POINTS
+            .iter()
+            .find(|(_, name)| name.eq_ignore_ascii_case(direction))
+            .map(|&(direction, _)| direction)
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        COMPASS_POINTS[self.index()].1
+    }
+
+    /// Bearing in degrees clockwise from north, e.g. 112.5 for ESE.
+    pub fn bearing(&self) -> f64 {
+        self.index() as f64 * 22.5
+    }
+
+    fn index(&self) -> usize {
+        COMPASS_POINTS
+            .iter()
+            .position(|(direction, _)| direction == self)
+            .unwrap_or_default()
+    }
+}
+
+/// The parts of a place description such as "198 km ESE of Kokopo, Papua New Guinea".
+///
+/// The epicenter lies `distance_km` in `direction` of `locality`. Descriptions naming only a
+/// place, like EMSC's "CRETE, GREECE", have neither distance nor direction.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlaceInfo {
+    pub distance_km: Option<f64>,
+    pub direction: Option<CompassDirection>,
+    pub locality: String,
+    // Country, or state or province for the US and Canada, as written, e.g. "CA" or "Alaska"
+    pub region: Option<String>,
+}
+
+impl PlaceInfo {
+    /// Parses `place`, or returns `None` for descriptions of other forms, such as
+    /// "south of the Fiji Islands" or "Central Mid-Atlantic Ridge".
+    pub fn parse(place: &str) -> Option<Self> {
+        let place = place.trim();
+        let (distance_km, direction, rest) = match parse_offset(place) {
+            Some((distance_km, direction, rest)) => (Some(distance_km), Some(direction), rest),
+            None => (None, None, place),
+        };
+
+        // The locality may itself contain commas, the region is after the last one
+        let (locality, region) = match rest.rsplit_once(',') {
+            Some((locality, region)) => (locality.trim(), Some(region.trim())),
+            None => (rest.trim(), None),
+        };
+        let region = region.filter(|region| !region.is_empty());
+
+        // Without an offset, only "locality, region" is recognizably a place
+        if locality.is_empty() || (distance_km.is_none() && region.is_none()) {
+            return None;
+        }
+
+        Some(PlaceInfo {
+            distance_km,
+            direction,
+            locality: locality.to_string(),
+            region: region.map(str::to_string),
+        })
+    }
+}
+
+// "198 km ESE of rest", also written "5km N of rest"
+fn parse_offset(place: &str) -> Option<(f64, CompassDirection, &str)> {
+    let (distance, rest) = place.split_once("km")?;
+    let distance_km: f64 = distance.trim().parse().ok()?;
+    if !distance_km.is_finite() || distance_km < 0.0 {
+        return None;
+    }
+
+    let (direction, rest) = rest.trim_start().split_once(' ')?;
+    let direction = CompassDirection::parse(direction)?;
+    let rest = rest.trim_start().strip_prefix("of ")?;
+
+    Some((distance_km, direction, rest))
+}
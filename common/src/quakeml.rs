@@ -0,0 +1,352 @@
+use chrono::{DateTime, Utc};
+use roxmltree::{Document, Node};
+
+use crate::earthquake_event::{Coordinates, EarthquakeEvent, OriginQuality};
+use crate::place::PlaceInfo;
+use crate::regionalization::FlinnEngdahlRegion;
+use crate::types::{EventType, MagnitudeType, ReviewStatus};
+use crate::utils;
+
+#[derive(thiserror::Error, Debug)]
+pub enum QuakeMlError {
+    #[error("invalid XML: {0}")]
+    Xml(#[from] roxmltree::Error),
+
+    #[error("event {event}: missing {element}")]
+    MissingElement {
+        event: String,
+        element: &'static str,
+    },
+
+    #[error("event {event}: invalid {element} value {value:?}")]
+    InvalidValue {
+        event: String,
+        element: &'static str,
+        value: String,
+    },
+
+    #[error("event {event}: {reason}")]
+    InvalidCoordinates { event: String, reason: String },
+}
+
+/// Parses a QuakeML 1.2 document into earthquake events.
+///
+/// Each event is built from its preferred origin and preferred magnitude, falling back to the
+/// first origin and magnitude when no preference is given. Events without an origin are skipped
+/// and events without a magnitude get a `NaN` magnitude.
+pub fn parse_quakeml(xml: &str) -> Result<Vec<EarthquakeEvent>, QuakeMlError> {
+    let document = Document::parse(xml)?;
+
+    document
+        .descendants()
+        .filter(|node| node.has_tag_name_local("event"))
+        .filter_map(|event| parse_event(event).transpose())
+        .collect()
+}
+
+fn parse_event(event: Node) -> Result<Option<EarthquakeEvent>, QuakeMlError> {
+    let public_id = event.attribute("publicID").unwrap_or_default().to_string();
+
+    let Some(origin) = preferred(event, "origin", "preferredOriginID") else {
+        return Ok(None);
+    };
+    let magnitude = preferred(event, "magnitude", "preferredMagnitudeID");
+
+    let time = origin
+        .child_path(&["time", "value"])
+        .ok_or_else(|| missing(&public_id, "origin time"))?;
+    let time = parse_time(&public_id, "origin time", time)?;
+
+    // QuakeML depths and horizontal uncertainties are given in meters
+    let coordinates = Coordinates {
+        lat: required_f64(&public_id, origin, &["latitude", "value"], "latitude")?,
+        lon: required_f64(&public_id, origin, &["longitude", "value"], "longitude")?,
+        depth: required_f64(&public_id, origin, &["depth", "value"], "depth")? / 1000.0,
+    };
+    coordinates
+        .validate()
+        .map_err(|reason| QuakeMlError::InvalidCoordinates {
+            event: public_id.clone(),
+            reason,
+        })?;
+
+    let updated = match event
+        .child_path(&["creationInfo", "creationTime"])
+        .or_else(|| origin.child_path(&["creationInfo", "creationTime"]))
+    {
+        Some(updated) => parse_time(&public_id, "creation time", updated)?,
+        None => time,
+    };
+
+    let quality = OriginQuality {
+        time_uncertainty_s: optional_f64(
+            &public_id,
+            origin,
+            &["time", "uncertainty"],
+            "time uncertainty",
+        )?,
+        latitude_uncertainty_deg: optional_f64(
+            &public_id,
+            origin,
+            &["latitude", "uncertainty"],
+            "latitude uncertainty",
+        )?,
+        longitude_uncertainty_deg: optional_f64(
+            &public_id,
+            origin,
+            &["longitude", "uncertainty"],
+            "longitude uncertainty",
+        )?,
+        horizontal_uncertainty_km: optional_f64(
+            &public_id,
+            origin,
+            &["originUncertainty", "horizontalUncertainty"],
+            "horizontal uncertainty",
+        )?
+        .map(|meters| meters / 1000.0),
+        depth_uncertainty_km: optional_f64(
+            &public_id,
+            origin,
+            &["depth", "uncertainty"],
+            "depth uncertainty",
+        )?
+        .map(|meters| meters / 1000.0),
+        associated_phase_count: optional_u32(
+            &public_id,
+            origin,
+            &["quality", "associatedPhaseCount"],
+            "associated phase count",
+        )?,
+        used_phase_count: optional_u32(
+            &public_id,
+            origin,
+            &["quality", "usedPhaseCount"],
+            "used phase count",
+        )?,
+        associated_station_count: optional_u32(
+            &public_id,
+            origin,
+            &["quality", "associatedStationCount"],
+            "associated station count",
+        )?,
+        used_station_count: optional_u32(
+            &public_id,
+            origin,
+            &["quality", "usedStationCount"],
+            "used station count",
+        )?,
+        standard_error: optional_f64(
+            &public_id,
+            origin,
+            &["quality", "standardError"],
+            "standard error",
+        )?,
+        azimuthal_gap: optional_f64(
+            &public_id,
+            origin,
+            &["quality", "azimuthalGap"],
+            "azimuthal gap",
+        )?,
+        minimum_distance_deg: optional_f64(
+            &public_id,
+            origin,
+            &["quality", "minimumDistance"],
+            "minimum distance",
+        )?,
+        mag_uncertainty: match magnitude {
+            Some(magnitude) => optional_f64(
+                &public_id,
+                magnitude,
+                &["mag", "uncertainty"],
+                "magnitude uncertainty",
+            )?,
+            None => None,
+        },
+        mag_station_count: match magnitude {
+            Some(magnitude) => optional_u32(
+                &public_id,
+                magnitude,
+                &["stationCount"],
+                "magnitude station count",
+            )?,
+            None => None,
+        },
+    };
+
+    let (mag, mag_type) = match magnitude {
+        Some(magnitude) => (
+            required_f64(&public_id, magnitude, &["mag", "value"], "magnitude")?,
+            magnitude
+                .child_path(&["type"])
+                .filter(|mag_type| !mag_type.trim().is_empty())
+                .map(MagnitudeType::from),
+        ),
+        None => (f64::NAN, None),
+    };
+
+    let place = event
+        .children()
+        .filter(|node| node.has_tag_name_local("description"))
+        .find(|description| {
+            matches!(
+                description.child_path(&["type"]),
+                Some("earthquake name") | Some("region name") | None
+            )
+        })
+        .and_then(|description| description.child_path(&["text"]))
+        .map(str::to_string);
+
+    // USGS splits its ids into catalog:eventsource and catalog:eventid attributes
+    let net = local_attribute(event, "eventsource").map(str::to_string);
+    let code = local_attribute(event, "eventid").map(str::to_string);
+    let id = match (&net, &code) {
+        (Some(net), Some(code)) => format!("{net}{code}"),
+        _ => public_id
+            .split_once("eventid=")
+            .map(|(_, id)| id.split('&').next().unwrap_or_default())
+            .unwrap_or(&public_id)
+            .to_string(),
+    };
+
+    // A rejected, reviewed or final status outranks the mode, which only says whether the
+    // solution was computed by hand
+    let status = match (
+        origin.child_path(&["evaluationStatus"]),
+        origin.child_path(&["evaluationMode"]),
+    ) {
+        (Some("rejected"), _) => Some(ReviewStatus::Deleted),
+        (Some("reviewed" | "final"), _) | (_, Some("manual")) => Some(ReviewStatus::Reviewed),
+        (_, Some(mode)) => Some(ReviewStatus::from(mode)),
+        (Some(status), None) => Some(ReviewStatus::from(status)),
+        (None, None) => None,
+    };
+
+    Ok(Some(EarthquakeEvent {
+        id,
+        mag,
+        place_info: place.as_deref().and_then(PlaceInfo::parse),
+        place,
+        time,
+        updated,
+        tsunami: 0,
+        region: FlinnEngdahlRegion::lookup(&coordinates),
+        coordinates,
+        mag_type,
+        event_type: event
+            .child_path(&["type"])
+            .map(EventType::from)
+            .unwrap_or_default(),
+        status,
+        net,
+        code,
+        nst: quality.used_station_count.map(|count| count as i32),
+        dmin: quality.minimum_distance_deg,
+        rms: quality.standard_error,
+        gap: quality.azimuthal_gap,
+        quality: Some(quality),
+        ..EarthquakeEvent::default()
+    }))
+}
+
+// Finds the child referenced by the preference element, or the first child of that kind
+fn preferred<'a, 'input>(
+    event: Node<'a, 'input>,
+    element: &str,
+    preference: &str,
+) -> Option<Node<'a, 'input>> {
+    let preferred_id = event.child_path(&[preference]);
+    let mut candidates = event
+        .children()
+        .filter(|node| node.has_tag_name_local(element));
+
+    match preferred_id {
+        Some(preferred_id) => candidates
+            .clone()
+            .find(|node| node.attribute("publicID") == Some(preferred_id))
+            .or_else(|| candidates.next()),
+        None => candidates.next(),
+    }
+}
+
+fn local_attribute<'a>(node: Node<'a, '_>, name: &str) -> Option<&'a str> {
+    node.attributes()
+        .find(|attribute| attribute.name() == name)
+        .map(|attribute| attribute.value())
+}
+
+// Some services leave the zone designator off, those times are UTC
+fn parse_time(
+    event: &str,
+    element: &'static str,
+    value: &str,
+) -> Result<DateTime<Utc>, QuakeMlError> {
+    utils::parse_time(value).ok_or_else(|| invalid(event, element, value))
+}
+
+fn required_f64(
+    event: &str,
+    node: Node,
+    path: &[&str],
+    element: &'static str,
+) -> Result<f64, QuakeMlError> {
+    optional_f64(event, node, path, element)?.ok_or_else(|| missing(event, element))
+}
+
+fn optional_f64(
+    event: &str,
+    node: Node,
+    path: &[&str],
+    element: &'static str,
+) -> Result<Option<f64>, QuakeMlError> {
+    node.child_path(path)
+        .map(|value| value.parse().map_err(|_| invalid(event, element, value)))
+        .transpose()
+}
+
+fn optional_u32(
+    event: &str,
+    node: Node,
+    path: &[&str],
+    element: &'static str,
+) -> Result<Option<u32>, QuakeMlError> {
+    node.child_path(path)
+        .map(|value| value.parse().map_err(|_| invalid(event, element, value)))
+        .transpose()
+}
+
+fn missing(event: &str, element: &'static str) -> QuakeMlError {
+    QuakeMlError::MissingElement {
+        event: event.to_string(),
+        element,
+    }
+}
+
+fn invalid(event: &str, element: &'static str, value: &str) -> QuakeMlError {
+    QuakeMlError::InvalidValue {
+        event: event.to_string(),
+        element,
+        value: value.to_string(),
+    }
+}
+
+// QuakeML documents mix namespaces, so elements are matched on their local name only
+trait NodeExt<'a, 'input> {
+    fn has_tag_name_local(&self, name: &str) -> bool;
+    fn child_path(&self, path: &[&str]) -> Option<&'a str>;
+}
+
+impl<'a, 'input> NodeExt<'a, 'input> for Node<'a, 'input> {
+    fn has_tag_name_local(&self, name: &str) -> bool {
+        self.is_element() && self.tag_name().name() == name
+    }
+
+    fn child_path(&self, path: &[&str]) -> Option<&'a str> {
+        let mut node = *self;
+        for name in path {
+            node = node
+                .children()
+                .find(|child| child.has_tag_name_local(name))?;
+        }
+        node.text().map(str::trim).filter(|text| !text.is_empty())
+    }
+}
@@ -0,0 +1,641 @@
+use std::cmp::Reverse;
+
+use chrono::{DateTime, Utc};
+
+use crate::coordinates::Coordinates;
+use crate::earthquake_event::EarthquakeEvent;
+use crate::geodesy::BoundingBox;
+use crate::types::EventType;
+use crate::utils::parse_time;
+
+/// Maximum number of events the USGS endpoint returns for a single query.
+pub const USGS_MAX_LIMIT: u32 = 20_000;
+
+// Response formats understood by the FDSN event web service
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Format {
+    #[default]
+    GeoJson,
+    Xml,
+    Csv,
+    Text,
+}
+
+impl Format {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Format::GeoJson => "geojson",
+            Format::Xml => "xml",
+            Format::Csv => "csv",
+            Format::Text => "text",
+        }
+    }
+
+    /// Guesses the format of a local catalog file from its extension.
+    pub fn from_extension(extension: &str) -> Option<Format> {
+        match extension.to_ascii_lowercase().as_str() {
+            "json" | "geojson" => Some(Format::GeoJson),
+            "xml" | "quakeml" => Some(Format::Xml),
+            "csv" => Some(Format::Csv),
+            "txt" | "text" => Some(Format::Text),
+            _ => None,
+        }
+    }
+}
+
+// Sort order of the returned events
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderBy {
+    Time,
+    TimeAsc,
+    Magnitude,
+    MagnitudeAsc,
+}
+
+impl OrderBy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OrderBy::Time => "time",
+            OrderBy::TimeAsc => "time-asc",
+            OrderBy::Magnitude => "magnitude",
+            OrderBy::MagnitudeAsc => "magnitude-asc",
+        }
+    }
+}
+
+// Geographic constraint of a query, either a lat/lon rectangle or a radius around a point
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Region {
+    Rectangle {
+        min_latitude: f64,
+        max_latitude: f64,
+        min_longitude: f64,
+        max_longitude: f64,
+    },
+    Circle {
+        latitude: f64,
+        longitude: f64,
+        min_radius_km: f64,
+        max_radius_km: f64,
+    },
+}
+
+impl Region {
+    pub fn contains(&self, coordinates: &Coordinates<f64>) -> bool {
+        match *self {
+            // Longitudes may run past ±180 to cross the antimeridian, e.g. 170 to 190
+            Region::Rectangle {
+                min_latitude,
+                max_latitude,
+                min_longitude,
+                max_longitude,
+            } => BoundingBox {
+                min_lat: min_latitude,
+                max_lat: max_latitude,
+                min_lon: min_longitude,
+                max_lon: max_longitude,
+            }
+            .contains(coordinates),
+            Region::Circle {
+                latitude,
+                longitude,
+                min_radius_km,
+                max_radius_km,
+            } => {
+                let center = Coordinates {
+                    lat: latitude,
+                    lon: longitude,
+                    depth: 0.0,
+                };
+                (min_radius_km..=max_radius_km).contains(&center.haversine_km(coordinates))
+            }
+        }
+    }
+}
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
+pub enum QueryError {
+    #[error("{name} must be between {min} and {max}, got {value}")]
+    OutOfRange {
+        name: &'static str,
+        value: f64,
+        min: f64,
+        max: f64,
+    },
+
+    #[error("{lower} must not be greater than {upper}")]
+    InvertedRange {
+        lower: &'static str,
+        upper: &'static str,
+    },
+
+    #[error("{0} must not be empty")]
+    EmptyParameter(&'static str),
+
+    #[error("invalid time: {0}")]
+    InvalidTime(String),
+
+    #[error("{0} is required")]
+    MissingParameter(&'static str),
+}
+
+/// A validated query against the FDSN event web service.
+///
+/// Use [`EventQuery::builder`] to construct one.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EventQuery {
+    format: Format,
+    start_time: Option<DateTime<Utc>>,
+    end_time: Option<DateTime<Utc>>,
+    min_magnitude: Option<f64>,
+    max_magnitude: Option<f64>,
+    min_depth: Option<f64>,
+    max_depth: Option<f64>,
+    region: Option<Region>,
+    order_by: Option<OrderBy>,
+    limit: Option<u32>,
+    offset: Option<u32>,
+    event_type: Option<EventType>,
+    catalog: Option<String>,
+    contributor: Option<String>,
+}
+
+impl EventQuery {
+    pub fn builder() -> EventQueryBuilder {
+        EventQueryBuilder::default()
+    }
+
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
+    pub fn start_time(&self) -> Option<DateTime<Utc>> {
+        self.start_time
+    }
+
+    pub fn end_time(&self) -> Option<DateTime<Utc>> {
+        self.end_time
+    }
+
+    pub fn min_magnitude(&self) -> Option<f64> {
+        self.min_magnitude
+    }
+
+    pub fn max_magnitude(&self) -> Option<f64> {
+        self.max_magnitude
+    }
+
+    pub fn min_depth(&self) -> Option<f64> {
+        self.min_depth
+    }
+
+    pub fn max_depth(&self) -> Option<f64> {
+        self.max_depth
+    }
+
+    pub fn region(&self) -> Option<Region> {
+        self.region
+    }
+
+    pub fn order_by(&self) -> Option<OrderBy> {
+        self.order_by
+    }
+
+    pub fn limit(&self) -> Option<u32> {
+        self.limit
+    }
+
+    pub fn offset(&self) -> Option<u32> {
+        self.offset
+    }
+
+    pub fn event_type(&self) -> Option<&EventType> {
+        self.event_type.as_ref()
+    }
+
+    pub fn catalog(&self) -> Option<&str> {
+        self.catalog.as_deref()
+    }
+
+    pub fn contributor(&self) -> Option<&str> {
+        self.contributor.as_deref()
+    }
+
+    /// Serializes the query into FDSN parameter name/value pairs, in a stable order.
+    pub fn to_query_pairs(&self) -> Vec<(&'static str, String)> {
+        let mut pairs = vec![("format", self.format.as_str().to_string())];
+
+        if let Some(start_time) = &self.start_time {
+            pairs.push(("starttime", format_query_time(start_time)));
+        }
+        if let Some(end_time) = &self.end_time {
+            pairs.push(("endtime", format_query_time(end_time)));
+        }
+        if let Some(min_magnitude) = self.min_magnitude {
+            pairs.push(("minmagnitude", min_magnitude.to_string()));
+        }
+        if let Some(max_magnitude) = self.max_magnitude {
+            pairs.push(("maxmagnitude", max_magnitude.to_string()));
+        }
+        if let Some(min_depth) = self.min_depth {
+            pairs.push(("mindepth", min_depth.to_string()));
+        }
+        if let Some(max_depth) = self.max_depth {
+            pairs.push(("maxdepth", max_depth.to_string()));
+        }
+        match self.region {
+            Some(Region::Rectangle {
+                min_latitude,
+                max_latitude,
+                min_longitude,
+                max_longitude,
+            }) => {
+                pairs.push(("minlatitude", min_latitude.to_string()));
+                pairs.push(("maxlatitude", max_latitude.to_string()));
+                pairs.push(("minlongitude", min_longitude.to_string()));
+                pairs.push(("maxlongitude", max_longitude.to_string()));
+            }
+            Some(Region::Circle {
+                latitude,
+                longitude,
+                min_radius_km,
+                max_radius_km,
+            }) => {
+                pairs.push(("latitude", latitude.to_string()));
+                pairs.push(("longitude", longitude.to_string()));
+                if min_radius_km > 0.0 {
+                    pairs.push(("minradiuskm", min_radius_km.to_string()));
+                }
+                pairs.push(("maxradiuskm", max_radius_km.to_string()));
+            }
+            None => {}
+        }
+        if let Some(order_by) = self.order_by {
+            pairs.push(("orderby", order_by.as_str().to_string()));
+        }
+        if let Some(limit) = self.limit {
+            pairs.push(("limit", limit.to_string()));
+        }
+        if let Some(offset) = self.offset {
+            pairs.push(("offset", offset.to_string()));
+        }
+        if let Some(event_type) = &self.event_type {
+            pairs.push(("eventtype", event_type.to_string()));
+        }
+        if let Some(catalog) = &self.catalog {
+            pairs.push(("catalog", catalog.clone()));
+        }
+        if let Some(contributor) = &self.contributor {
+            pairs.push(("contributor", contributor.clone()));
+        }
+
+        pairs
+    }
+
+    /// Serializes the query for the `count` endpoint, which ignores paging, ordering and format.
+    pub fn to_count_pairs(&self) -> Vec<(&'static str, String)> {
+        self.to_query_pairs()
+            .into_iter()
+            .filter(|(name, _)| !matches!(*name, "format" | "orderby" | "limit" | "offset"))
+            .collect()
+    }
+
+    /// Whether `event` passes the filters of the query, as the web service would decide.
+    ///
+    /// Time bounds are inclusive. Comparisons with a missing (NaN) magnitude are false, so such
+    /// events only pass when no magnitude bound is set.
+    pub fn matches(&self, event: &EarthquakeEvent) -> bool {
+        let coordinates = &event.coordinates;
+
+        self.start_time
+            .map_or(true, |start_time| event.time >= start_time)
+            && self
+                .end_time
+                .map_or(true, |end_time| event.time <= end_time)
+            && self
+                .min_magnitude
+                .map_or(true, |min_magnitude| event.mag >= min_magnitude)
+            && self
+                .max_magnitude
+                .map_or(true, |max_magnitude| event.mag <= max_magnitude)
+            && self
+                .min_depth
+                .map_or(true, |min_depth| coordinates.depth >= min_depth)
+            && self
+                .max_depth
+                .map_or(true, |max_depth| coordinates.depth <= max_depth)
+            && self
+                .region
+                .map_or(true, |region| region.contains(coordinates))
+            && self.event_type().map_or(true, |event_type| {
+                event
+                    .event_type
+                    .as_str()
+                    .eq_ignore_ascii_case(event_type.as_str())
+            })
+            && self
+                .catalog()
+                .map_or(true, |catalog| event.net.as_deref() == Some(catalog))
+            && self.contributor().map_or(true, |contributor| {
+                event.sources.iter().any(|source| source == contributor)
+            })
+    }
+
+    /// Answers the query from events held in memory: filters, orders and pages them the way
+    /// the web service would.
+    pub fn select(&self, events: Vec<EarthquakeEvent>) -> Vec<EarthquakeEvent> {
+        let events = events
+            .into_iter()
+            .filter(|event| self.matches(event))
+            .collect();
+
+        // The web service returns the newest events first unless asked otherwise
+        self.arrange(events, OrderBy::Time)
+    }
+
+    // Orders the events by `orderby`, or `default_order` without one, and applies the offset
+    // and limit
+    pub(crate) fn arrange(
+        &self,
+        mut events: Vec<EarthquakeEvent>,
+        default_order: OrderBy,
+    ) -> Vec<EarthquakeEvent> {
+        match self.order_by.unwrap_or(default_order) {
+            OrderBy::Time => events.sort_by_key(|event| Reverse(event.time)),
+            OrderBy::TimeAsc => events.sort_by_key(|event| event.time),
+            OrderBy::Magnitude => events.sort_by(|a, b| b.mag.total_cmp(&a.mag)),
+            OrderBy::MagnitudeAsc => events.sort_by(|a, b| a.mag.total_cmp(&b.mag)),
+        }
+
+        // Offsets count from 1
+        let offset = self.offset.map_or(0, |offset| offset.saturating_sub(1)) as usize;
+        let limit = self.limit.map_or(usize::MAX, |limit| limit as usize);
+        events.into_iter().skip(offset).take(limit).collect()
+    }
+
+    // A copy without `orderby`, `limit` and `offset`, for queries that are split up and merged
+    pub(crate) fn without_paging(&self) -> Self {
+        Self {
+            order_by: None,
+            limit: None,
+            offset: None,
+            ..self.clone()
+        }
+    }
+
+    /// Returns a copy of the query restricted to the given time window.
+    pub fn with_time_range(&self, start_time: DateTime<Utc>, end_time: DateTime<Utc>) -> Self {
+        Self {
+            start_time: Some(start_time),
+            end_time: Some(end_time),
+            ..self.clone()
+        }
+    }
+
+    fn validate(&self) -> Result<(), QueryError> {
+        if let (Some(start_time), Some(end_time)) = (self.start_time, self.end_time) {
+            if start_time >= end_time {
+                return Err(QueryError::InvertedRange {
+                    lower: "starttime",
+                    upper: "endtime",
+                });
+            }
+        }
+
+        // The magnitude scales are open-ended, these bounds only keep out NaN and nonsense
+        if let Some(min_magnitude) = self.min_magnitude {
+            check_range("minmagnitude", min_magnitude, -10.0, 10.0)?;
+        }
+        if let Some(max_magnitude) = self.max_magnitude {
+            check_range("maxmagnitude", max_magnitude, -10.0, 10.0)?;
+        }
+        check_ordered(
+            "minmagnitude",
+            self.min_magnitude,
+            "maxmagnitude",
+            self.max_magnitude,
+        )?;
+
+        if let Some(min_depth) = self.min_depth {
+            check_range("mindepth", min_depth, -100.0, 1000.0)?;
+        }
+        if let Some(max_depth) = self.max_depth {
+            check_range("maxdepth", max_depth, -100.0, 1000.0)?;
+        }
+        check_ordered("mindepth", self.min_depth, "maxdepth", self.max_depth)?;
+
+        match self.region {
+            Some(Region::Rectangle {
+                min_latitude,
+                max_latitude,
+                min_longitude,
+                max_longitude,
+            }) => {
+                check_range("minlatitude", min_latitude, -90.0, 90.0)?;
+                check_range("maxlatitude", max_latitude, -90.0, 90.0)?;
+                check_range("minlongitude", min_longitude, -360.0, 360.0)?;
+                check_range("maxlongitude", max_longitude, -360.0, 360.0)?;
+                check_ordered(
+                    "minlatitude",
+                    Some(min_latitude),
+                    "maxlatitude",
+                    Some(max_latitude),
+                )?;
+                check_ordered(
+                    "minlongitude",
+                    Some(min_longitude),
+                    "maxlongitude",
+                    Some(max_longitude),
+                )?;
+            }
+            Some(Region::Circle {
+                latitude,
+                longitude,
+                min_radius_km,
+                max_radius_km,
+            }) => {
+                check_range("latitude", latitude, -90.0, 90.0)?;
+                check_range("longitude", longitude, -180.0, 180.0)?;
+                check_range("minradiuskm", min_radius_km, 0.0, 20001.6)?;
+                check_range("maxradiuskm", max_radius_km, 0.0, 20001.6)?;
+                check_ordered(
+                    "minradiuskm",
+                    Some(min_radius_km),
+                    "maxradiuskm",
+                    Some(max_radius_km),
+                )?;
+            }
+            None => {}
+        }
+
+        if let Some(limit) = self.limit {
+            check_range("limit", limit as f64, 1.0, USGS_MAX_LIMIT as f64)?;
+        }
+        if let Some(offset) = self.offset {
+            check_range("offset", offset as f64, 1.0, u32::MAX as f64)?;
+        }
+
+        for (name, value) in [
+            ("eventtype", self.event_type.as_ref().map(EventType::as_str)),
+            ("catalog", self.catalog.as_deref()),
+            ("contributor", self.contributor.as_deref()),
+        ] {
+            if value.is_some_and(|value| value.trim().is_empty()) {
+                return Err(QueryError::EmptyParameter(name));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct EventQueryBuilder {
+    query: EventQuery,
+}
+
+impl EventQueryBuilder {
+    pub fn format(mut self, format: Format) -> Self {
+        self.query.format = format;
+        self
+    }
+
+    pub fn start_time(mut self, start_time: DateTime<Utc>) -> Self {
+        self.query.start_time = Some(start_time);
+        self
+    }
+
+    pub fn end_time(mut self, end_time: DateTime<Utc>) -> Self {
+        self.query.end_time = Some(end_time);
+        self
+    }
+
+    /// Sets the start and end time from strings such as `2014-01-01` or `2014-01-01T12:00:00`.
+    pub fn time_range(mut self, start_time: &str, end_time: &str) -> Result<Self, QueryError> {
+        self.query.start_time = Some(
+            parse_time(start_time)
+                .ok_or_else(|| QueryError::InvalidTime(start_time.to_string()))?,
+        );
+        self.query.end_time = Some(
+            parse_time(end_time).ok_or_else(|| QueryError::InvalidTime(end_time.to_string()))?,
+        );
+        Ok(self)
+    }
+
+    pub fn min_magnitude(mut self, min_magnitude: f64) -> Self {
+        self.query.min_magnitude = Some(min_magnitude);
+        self
+    }
+
+    pub fn max_magnitude(mut self, max_magnitude: f64) -> Self {
+        self.query.max_magnitude = Some(max_magnitude);
+        self
+    }
+
+    pub fn min_depth(mut self, min_depth_km: f64) -> Self {
+        self.query.min_depth = Some(min_depth_km);
+        self
+    }
+
+    pub fn max_depth(mut self, max_depth_km: f64) -> Self {
+        self.query.max_depth = Some(max_depth_km);
+        self
+    }
+
+    pub fn rectangle(
+        mut self,
+        min_latitude: f64,
+        max_latitude: f64,
+        min_longitude: f64,
+        max_longitude: f64,
+    ) -> Self {
+        self.query.region = Some(Region::Rectangle {
+            min_latitude,
+            max_latitude,
+            min_longitude,
+            max_longitude,
+        });
+        self
+    }
+
+    pub fn circle(mut self, latitude: f64, longitude: f64, max_radius_km: f64) -> Self {
+        self.query.region = Some(Region::Circle {
+            latitude,
+            longitude,
+            min_radius_km: 0.0,
+            max_radius_km,
+        });
+        self
+    }
+
+    pub fn region(mut self, region: Region) -> Self {
+        self.query.region = Some(region);
+        self
+    }
+
+    pub fn order_by(mut self, order_by: OrderBy) -> Self {
+        self.query.order_by = Some(order_by);
+        self
+    }
+
+    pub fn limit(mut self, limit: u32) -> Self {
+        self.query.limit = Some(limit);
+        self
+    }
+
+    pub fn offset(mut self, offset: u32) -> Self {
+        self.query.offset = Some(offset);
+        self
+    }
+
+    pub fn event_type(mut self, event_type: impl Into<EventType>) -> Self {
+        self.query.event_type = Some(event_type.into());
+        self
+    }
+
+    pub fn catalog(mut self, catalog: impl Into<String>) -> Self {
+        self.query.catalog = Some(catalog.into());
+        self
+    }
+
+    pub fn contributor(mut self, contributor: impl Into<String>) -> Self {
+        self.query.contributor = Some(contributor.into());
+        self
+    }
+
+    pub fn build(self) -> Result<EventQuery, QueryError> {
+        self.query.validate()?;
+        Ok(self.query)
+    }
+}
+
+// FDSN accepts ISO-8601 without a timezone designator, interpreted as UTC
+fn format_query_time(time: &DateTime<Utc>) -> String {
+    time.format("%Y-%m-%dT%H:%M:%S%.3f").to_string()
+}
+
+fn check_range(name: &'static str, value: f64, min: f64, max: f64) -> Result<(), QueryError> {
+    if value.is_nan() || value < min || value > max {
+        return Err(QueryError::OutOfRange {
+            name,
+            value,
+            min,
+            max,
+        });
+    }
+    Ok(())
+}
+
+fn check_ordered(
+    lower: &'static str,
+    lower_value: Option<f64>,
+    upper: &'static str,
+    upper_value: Option<f64>,
+) -> Result<(), QueryError> {
+    if let (Some(lower_value), Some(upper_value)) = (lower_value, upper_value) {
+        if lower_value > upper_value {
+            return Err(QueryError::InvertedRange { lower, upper });
+        }
+    }
+    Ok(())
+}
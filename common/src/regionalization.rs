@@ -0,0 +1,107 @@
+use serde::{Deserialize, Serialize};
+
+use crate::coordinates::Coordinates;
+
+// Number, name and the inclusive ranges of geographic region numbers it covers
+type SeismicRegion = (u8, &'static str, &'static [(u16, u16)]);
+
+// The 1974 scheme numbers geographic regions 1-729 consecutively within each seismic region;
+// the 1995 revision added 730-757, each of which lies within the seismic region listed here.
+const SEISMIC_REGIONS: [SeismicRegion; 50] = [
+    (1, "ALASKA-ALEUTIAN ARC", &[(1, 17)]),
+    (2, "EASTERN ALASKA TO VANCOUVER ISLAND", &[(18, 25)]),
+    (3, "CALIFORNIA-NEVADA REGION", &[(26, 44)]),
+    (4, "BAJA CALIFORNIA AND GULF OF CALIFORNIA", &[(45, 50)]),
+    (5, "MEXICO-GUATEMALA AREA", &[(51, 71), (730, 730)]),
+    (6, "CENTRAL AMERICA", &[(72, 83)]),
+    (7, "CARIBBEAN LOOP", &[(84, 101), (731, 731)]),
+    (8, "ANDEAN SOUTH AMERICA", &[(102, 146)]),
+    (9, "EXTREME SOUTH AMERICA", &[(147, 149)]),
+    (10, "SOUTHERN ANTILLES", &[(150, 157), (732, 732)]),
+    (11, "NEW ZEALAND REGION", &[(158, 168)]),
+    (12, "KERMADEC-TONGA-SAMOA AREA", &[(169, 179)]),
+    (13, "FIJI ISLANDS AREA", &[(180, 182)]),
+    (14, "NEW HEBRIDES ISLANDS", &[(183, 189)]),
+    (15, "BISMARCK AND SOLOMON ISLANDS", &[(190, 195)]),
+    (16, "NEW GUINEA", &[(196, 208)]),
+    (17, "CAROLINE ISLANDS AREA", &[(209, 210)]),
+    (18, "GUAM TO JAPAN", &[(211, 216)]),
+    (19, "JAPAN-KURILS-KAMCHATKA", &[(217, 230)]),
+    (20, "SOUTHWESTERN JAPAN AND RYUKYU ISLANDS", &[(231, 241)]),
+    (21, "TAIWAN AREA", &[(242, 247)]),
+    (22, "PHILIPPINE ISLANDS", &[(248, 260)]),
+    (23, "BORNEO-SULAWESI", &[(261, 272)]),
+    (24, "SUNDA ARC", &[(273, 293)]),
+    (25, "MYANMAR AND SOUTHEAST ASIA", &[(294, 301), (733, 737)]),
+    (26, "INDIA-XIZANG-SICHUAN-YUNNAN", &[(302, 319)]),
+    (27, "SOUTHERN XINJIANG TO GANSU", &[(320, 325)]),
+    (28, "LAKE ISSYK-KUL TO LAKE BAYKAL", &[(326, 334)]),
+    (29, "WESTERN ASIA", &[(335, 356)]),
+    (30, "MIDDLE EAST-CRIMEA-BALKANS", &[(357, 375)]),
+    (31, "WESTERN MEDITERRANEAN AREA", &[(376, 401)]),
+    (32, "ATLANTIC OCEAN", &[(402, 414), (738, 739)]),
+    (33, "INDIAN OCEAN", &[(415, 437), (740, 742)]),
+    (34, "EASTERN NORTH AMERICA", &[(438, 527)]),
+    (35, "EASTERN SOUTH AMERICA", &[(528, 531)]),
+    (36, "NORTHWESTERN EUROPE", &[(532, 549)]),
+    (37, "AFRICA", &[(550, 587), (743, 755)]),
+    (38, "AUSTRALIA", &[(588, 610)]),
+    (39, "PACIFIC BASIN", &[(611, 632)]),
+    (40, "ARCTIC ZONE", &[(633, 655)]),
+    (41, "EASTERN ASIA", &[(656, 666)]),
+    (
+        42,
+        "NORTHEASTERN ASIA, NORTHERN ALASKA TO GREENLAND",
+        &[(667, 682)],
+    ),
+    (
+        43,
+        "SOUTHEASTERN AND ANTARCTIC PACIFIC OCEAN",
+        &[(683, 692), (756, 756)],
+    ),
+    (44, "GALAPAGOS AREA", &[(693, 699), (757, 757)]),
+    (45, "MACQUARIE LOOP", &[(700, 702)]),
+    (46, "ANDAMAN ISLANDS TO SUMATRA", &[(703, 708)]),
+    (47, "BALUCHISTAN", &[(709, 712)]),
+    (48, "HINDU KUSH AND PAMIR AREA", &[(713, 720)]),
+    (49, "NORTHERN ASIA", &[(721, 726)]),
+    (50, "ANTARCTICA", &[(727, 729)]),
+];
+
+/// A Flinn-Engdahl region, after the 1995 revision (Young et al., 1996).
+///
+/// Geographic regions are the 757 named areas USGS and EMSC use in event titles; they group
+/// into 50 seismic regions of related seismicity. Regions are looked up offline from the
+/// regionalization tables, so they make a stable grouping key for statistics.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct FlinnEngdahlRegion {
+    pub geographic_number: u16,
+    pub geographic_name: String,
+    pub seismic_number: u8,
+    pub seismic_name: String,
+}
+
+impl FlinnEngdahlRegion {
+    /// The region of an epicenter, or `None` if its latitude or longitude is out of range.
+    pub fn lookup(coordinates: &Coordinates<f64>) -> Option<Self> {
+        let Coordinates { lat, lon, .. } = *coordinates;
+        if !lat.is_finite() || !lon.is_finite() {
+            return None;
+        }
+        let geographic_number = u16::try_from(flinn_engdahl::region_number(lat, lon).ok()?).ok()?;
+        let geographic_name = flinn_engdahl::region(lat, lon).ok()?;
+        let &(seismic_number, seismic_name, _) =
+            SEISMIC_REGIONS.iter().find(|(_, _, geographic_numbers)| {
+                geographic_numbers
+                    .iter()
+                    .any(|&(first, last)| (first..=last).contains(&geographic_number))
+            })?;
+
+        Some(FlinnEngdahlRegion {
+            geographic_number,
+            geographic_name: geographic_name.to_string(),
+            seismic_number,
+            seismic_name: seismic_name.to_string(),
+        })
+    }
+}
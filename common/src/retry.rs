@@ -0,0 +1,191 @@
+use std::future::Future;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use reqwest::header::RETRY_AFTER;
+use reqwest::{Client, Request, Response, StatusCode};
+use tracing::Span;
+
+use crate::earthquake_event::Errors;
+
+/// How failed requests are retried.
+///
+/// Only transient errors are retried, see [`Errors::is_transient`]: transport failures (timeouts,
+/// refused or reset connections) and the statuses 429, 500, 502, 503 and 504. All requests made by the data sources are GETs, so repeating
+/// them is safe. The delay before attempt `n + 1` is drawn uniformly from
+/// `[0, initial_backoff * 2^(n - 1)]`, capped at `max_backoff`. A `Retry-After` header takes
+/// precedence; if it asks for more than `max_backoff` the request is not retried. Bodies that
+/// break off while being read are retried like transport failures.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    jitter: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 4,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            jitter: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    // A policy that makes a single attempt
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    pub fn max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    pub fn initial_backoff(mut self, initial_backoff: Duration) -> Self {
+        self.initial_backoff = initial_backoff;
+        self
+    }
+
+    pub fn max_backoff(mut self, max_backoff: Duration) -> Self {
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    pub fn jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Executes `request`, retrying transient failures.
+    ///
+    /// Only getting the response is retried, see [`RetryPolicy::execute_with`] to also retry
+    /// reading its body. When the attempts are exhausted the last response or error is returned.
+    pub async fn execute(&self, client: &Client, request: Request) -> Result<Response, Errors> {
+        self.execute_with(client, request, |response| async { Ok(response) })
+            .await
+    }
+
+    /// Executes `request` and hands the response to `read`, retrying transient failures of
+    /// either. A body cut off by a reset connection or a timeout is requested again.
+    ///
+    /// Responses of a retryable status are only passed to `read` once they are no longer
+    /// retried. The number of attempts is recorded in the `attempts` field of the current span.
+    pub async fn execute_with<T, F, Fut>(
+        &self,
+        client: &Client,
+        request: Request,
+        read: F,
+    ) -> Result<T, Errors>
+    where
+        F: Fn(Response) -> Fut,
+        Fut: Future<Output = Result<T, Errors>>,
+    {
+        let mut request = request;
+        let mut attempt = 1;
+
+        loop {
+            // Last attempt, or a body that cannot be sent twice
+            let Some(next_request) = (attempt < self.max_attempts)
+                .then(|| request.try_clone())
+                .flatten()
+            else {
+                Span::current().record("attempts", attempt);
+                return read(client.execute(request).await?).await;
+            };
+
+            let delay = match client.execute(request).await {
+                Ok(response) => match self.delay_for_response(&response, attempt) {
+                    Some(delay) => delay,
+                    None => match read(response).await {
+                        Err(error) if is_interrupted(&error) => self.backoff(attempt),
+                        result => {
+                            Span::current().record("attempts", attempt);
+                            return result;
+                        }
+                    },
+                },
+                Err(error) => {
+                    let error = Errors::from(error);
+                    if !error.is_transient() {
+                        Span::current().record("attempts", attempt);
+                        return Err(error);
+                    }
+                    self.backoff(attempt)
+                }
+            };
+
+            tracing::warn!(attempt, ?delay, "Retrying request");
+            tokio::time::sleep(delay).await;
+            request = next_request;
+            attempt += 1;
+        }
+    }
+
+    fn delay_for_response(&self, response: &Response, attempt: u32) -> Option<Duration> {
+        if !is_retryable_status(response.status()) {
+            return None;
+        }
+
+        match retry_after(response) {
+            Some(retry_after) if retry_after > self.max_backoff => None,
+            Some(retry_after) => Some(retry_after),
+            None => Some(self.backoff(attempt)),
+        }
+    }
+
+    fn backoff(&self, attempt: u32) -> Duration {
+        let exponential = self
+            .initial_backoff
+            .saturating_mul(2u32.saturating_pow(attempt - 1))
+            .min(self.max_backoff);
+
+        if self.jitter {
+            let millis = exponential.as_millis() as u64;
+            Duration::from_millis(rand::thread_rng().gen_range(0..=millis))
+        } else {
+            exponential
+        }
+    }
+}
+
+pub(crate) fn is_retryable_status(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::TOO_MANY_REQUESTS
+            | StatusCode::INTERNAL_SERVER_ERROR
+            | StatusCode::BAD_GATEWAY
+            | StatusCode::SERVICE_UNAVAILABLE
+            | StatusCode::GATEWAY_TIMEOUT
+    )
+}
+
+// The body broke off while it was being read. Statuses were already judged before reading, with
+// their Retry-After, so they are not retried again here.
+fn is_interrupted(error: &Errors) -> bool {
+    !matches!(error, Errors::Status { .. }) && error.is_transient()
+}
+
+// Retry-After is either a number of seconds or an HTTP date
+fn retry_after(response: &Response) -> Option<Duration> {
+    let value = response.headers().get(RETRY_AFTER)?.to_str().ok()?.trim();
+
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let date = DateTime::parse_from_rfc2822(value).ok()?;
+    Some(
+        (date.with_timezone(&Utc) - Utc::now())
+            .to_std()
+            .unwrap_or_default(),
+    )
+}
@@ -0,0 +1,193 @@
+use std::collections::VecDeque;
+use std::pin::Pin;
+
+use futures::{Stream, StreamExt};
+use serde::de::Error as _;
+
+use crate::earthquake_event::{decode_feature, EarthquakeEvent, Errors};
+
+/// Incremental decoder for a GeoJSON FeatureCollection.
+///
+/// Bytes are fed in as they arrive and each feature of the top-level `features` array is
+/// decoded as soon as its closing brace is seen, so only one feature is buffered at a time.
+/// Everything outside that array is skipped without being parsed.
+#[derive(Debug, Default)]
+pub struct GeoJsonDecoder {
+    // Nesting depth of objects and arrays
+    depth: usize,
+    in_string: bool,
+    escaped: bool,
+    // Last string seen directly inside the top-level object, i.e. the current key
+    key: Vec<u8>,
+    collecting_key: bool,
+    in_features: bool,
+    feature: Option<Vec<u8>>,
+    index: usize,
+    seen_collection: bool,
+    failed: bool,
+}
+
+impl GeoJsonDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Consumes the next chunk of the body and returns the features it completed. After a
+    /// syntax error the decoder returns that error once and ignores further input.
+    pub fn push(&mut self, chunk: &[u8]) -> Vec<Result<EarthquakeEvent, Errors>> {
+        let mut events = Vec::new();
+        if self.failed {
+            return events;
+        }
+
+        for &byte in chunk {
+            if let Some(feature) = &mut self.feature {
+                feature.push(byte);
+            }
+
+            if self.in_string {
+                match byte {
+                    _ if self.escaped => self.escaped = false,
+                    b'\\' => self.escaped = true,
+                    b'"' => {
+                        self.in_string = false;
+                        self.collecting_key = false;
+                    }
+                    _ => {}
+                }
+                if self.collecting_key {
+                    self.key.push(byte);
+                }
+                continue;
+            }
+
+            match byte {
+                b'"' => {
+                    self.in_string = true;
+                    if self.depth == 1 {
+                        self.collecting_key = true;
+                        self.key.clear();
+                    }
+                }
+                b'{' | b'[' => {
+                    if self.depth == 0 {
+                        if byte == b'[' {
+                            return self.fail(events, "expected a FeatureCollection object");
+                        }
+                        self.seen_collection = true;
+                    }
+                    self.depth += 1;
+                    if byte == b'[' && self.depth == 2 && self.key == b"features" {
+                        self.in_features = true;
+                    }
+                    if byte == b'{' && self.in_features && self.depth == 3 {
+                        self.feature = Some(vec![b'{']);
+                    }
+                }
+                b'}' | b']' => {
+                    let Some(depth) = self.depth.checked_sub(1) else {
+                        return self.fail(events, "unbalanced closing bracket");
+                    };
+                    self.depth = depth;
+                    if self.in_features && depth == 2 && byte == b'}' {
+                        if let Some(feature) = self.feature.take() {
+                            events.push(self.decode(&feature));
+                        }
+                    }
+                    if self.in_features && depth == 1 {
+                        self.in_features = false;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        events
+    }
+
+    /// Checks that the body ended with the FeatureCollection closed.
+    pub fn finish(&self) -> Result<(), Errors> {
+        if self.failed || (self.seen_collection && self.depth == 0) {
+            Ok(())
+        } else {
+            Err(Errors::GeoJson(serde_json::Error::custom(
+                "GeoJSON body ended before the FeatureCollection was closed",
+            )))
+        }
+    }
+
+    pub fn is_failed(&self) -> bool {
+        self.failed
+    }
+
+    fn decode(&mut self, feature: &[u8]) -> Result<EarthquakeEvent, Errors> {
+        let index = self.index;
+        self.index += 1;
+        decode_feature(index, serde_json::from_slice(feature)?)
+    }
+
+    fn fail(
+        &mut self,
+        mut events: Vec<Result<EarthquakeEvent, Errors>>,
+        message: &str,
+    ) -> Vec<Result<EarthquakeEvent, Errors>> {
+        self.failed = true;
+        self.feature = None;
+        events.push(Err(Errors::GeoJson(serde_json::Error::custom(message))));
+        events
+    }
+}
+
+/// Decodes a GeoJSON FeatureCollection from a stream of byte chunks, such as
+/// `reqwest::Response::bytes_stream`, yielding each event as soon as it is complete.
+///
+/// A feature that fails to decode yields an error and decoding carries on with the next one;
+/// transport and syntax errors end the stream.
+pub fn decode_geojson<S, B, E>(bytes: S) -> impl Stream<Item = Result<EarthquakeEvent, Errors>>
+where
+    S: Stream<Item = Result<B, E>>,
+    B: AsRef<[u8]>,
+    Errors: From<E>,
+{
+    struct State<S> {
+        bytes: Pin<Box<S>>,
+        decoder: GeoJsonDecoder,
+        ready: VecDeque<Result<EarthquakeEvent, Errors>>,
+        done: bool,
+    }
+
+    let state = State {
+        bytes: Box::pin(bytes),
+        decoder: GeoJsonDecoder::new(),
+        ready: VecDeque::new(),
+        done: false,
+    };
+
+    futures::stream::unfold(state, |mut state| async move {
+        loop {
+            if let Some(item) = state.ready.pop_front() {
+                return Some((item, state));
+            }
+            if state.done {
+                return None;
+            }
+
+            match state.bytes.next().await {
+                Some(Ok(chunk)) => {
+                    state.ready.extend(state.decoder.push(chunk.as_ref()));
+                    state.done = state.decoder.is_failed();
+                }
+                Some(Err(error)) => {
+                    state.done = true;
+                    state.ready.push_back(Err(error.into()));
+                }
+                None => {
+                    state.done = true;
+                    if let Err(error) = state.decoder.finish() {
+                        state.ready.push_back(Err(error));
+                    }
+                }
+            }
+        }
+    })
+}
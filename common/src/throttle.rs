@@ -0,0 +1,157 @@
+use std::env;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use tokio::sync::{Semaphore, SemaphorePermit};
+use tokio::time::Instant;
+use tracing::{instrument, Span};
+
+use crate::earthquake_event::{CountableDataSource, EarthquakeDataSource, EarthquakeEvent};
+use crate::query::EventQuery;
+
+const MAX_IN_FLIGHT_VAR: &str = "EARTHQUAKE_MAX_IN_FLIGHT";
+const REQUESTS_PER_SECOND_VAR: &str = "EARTHQUAKE_REQUESTS_PER_SECOND";
+
+// Politeness settings for a catalog service
+#[derive(Debug, Clone, PartialEq)]
+pub struct ThrottleConfig {
+    pub max_in_flight: usize,
+    // Zero, negative and NaN rates turn the rate limit off
+    pub requests_per_second: f64,
+}
+
+impl Default for ThrottleConfig {
+    fn default() -> Self {
+        Self {
+            max_in_flight: 4,
+            requests_per_second: 5.0,
+        }
+    }
+}
+
+impl ThrottleConfig {
+    /// Reads `EARTHQUAKE_MAX_IN_FLIGHT` and `EARTHQUAKE_REQUESTS_PER_SECOND`, keeping the
+    /// defaults for unset or unparsable values. A rate of zero or less turns the rate limit off.
+    pub fn from_env() -> Self {
+        let default = Self::default();
+        Self {
+            max_in_flight: env::var(MAX_IN_FLIGHT_VAR)
+                .ok()
+                .and_then(|value| value.parse().ok())
+                .filter(|&max_in_flight| max_in_flight > 0)
+                .unwrap_or(default.max_in_flight),
+            requests_per_second: env::var(REQUESTS_PER_SECOND_VAR)
+                .ok()
+                .and_then(|value| value.parse().ok())
+                .unwrap_or(default.requests_per_second),
+        }
+    }
+}
+
+/// A rate limiter and concurrency limit, shared by every clone.
+#[derive(Debug, Clone)]
+pub struct Throttle {
+    inner: Arc<ThrottleInner>,
+}
+
+#[derive(Debug)]
+struct ThrottleInner {
+    semaphore: Semaphore,
+    interval: Duration,
+    // `None` once a rate too low to represent has used up its only slot
+    next_slot: Mutex<Option<Instant>>,
+}
+
+impl Throttle {
+    pub fn new(config: &ThrottleConfig) -> Self {
+        Self {
+            inner: Arc::new(ThrottleInner {
+                semaphore: Semaphore::new(config.max_in_flight.max(1)),
+                interval: interval(config.requests_per_second),
+                next_slot: Mutex::new(Some(Instant::now())),
+            }),
+        }
+    }
+
+    /// Waits for a free slot and the next request time, recording the wait in the `queue_ms`
+    /// field of the current span. The slot is released when the permit is dropped.
+    pub async fn acquire(&self) -> SemaphorePermit<'_> {
+        let queued_at = Instant::now();
+        let permit = self
+            .inner
+            .semaphore
+            .acquire()
+            .await
+            .expect("the throttle semaphore is never closed");
+
+        // Requests are spaced at least `interval` apart
+        let slot = {
+            let mut next_slot = self.inner.next_slot.lock().unwrap();
+            let slot = next_slot.map(|next_slot| next_slot.max(Instant::now()));
+            *next_slot = slot.and_then(|slot| slot.checked_add(self.inner.interval));
+            slot
+        };
+        match slot {
+            Some(slot) => tokio::time::sleep_until(slot).await,
+            None => std::future::pending().await,
+        }
+
+        Span::current().record("queue_ms", queued_at.elapsed().as_millis() as u64);
+        permit
+    }
+}
+
+fn interval(requests_per_second: f64) -> Duration {
+    if requests_per_second > 0.0 {
+        Duration::try_from_secs_f64(requests_per_second.recip()).unwrap_or(Duration::MAX)
+    } else {
+        Duration::ZERO
+    }
+}
+
+/// Wraps a data source so every call goes through a [`Throttle`].
+#[derive(Debug, Clone)]
+pub struct Throttled<S> {
+    source: S,
+    throttle: Throttle,
+}
+
+impl<S> Throttled<S> {
+    pub fn new(source: S, throttle: Throttle) -> Self {
+        Self { source, throttle }
+    }
+
+    pub fn source(&self) -> &S {
+        &self.source
+    }
+}
+
+#[async_trait]
+impl<S> EarthquakeDataSource for Throttled<S>
+where
+    S: EarthquakeDataSource + Sync,
+{
+    type Error = S::Error;
+
+    #[instrument(skip_all, fields(queue_ms))]
+    async fn fetch_earthquake_data(
+        &self,
+        query: &EventQuery,
+    ) -> Result<Vec<EarthquakeEvent>, S::Error> {
+        let _permit = self.throttle.acquire().await;
+        self.source.fetch_earthquake_data(query).await
+    }
+}
+
+#[async_trait]
+impl<S> CountableDataSource for Throttled<S>
+where
+    S: CountableDataSource + Sync,
+{
+    #[instrument(skip_all, fields(queue_ms))]
+    async fn count_events(&self, query: &EventQuery) -> Result<u64, S::Error> {
+        let _permit = self.throttle.acquire().await;
+        self.source.count_events(query).await
+    }
+}
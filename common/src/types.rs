@@ -0,0 +1,202 @@
+use std::fmt;
+
+use diesel::backend::Backend;
+use diesel::deserialize::{self, FromSql, FromSqlRow};
+use diesel::expression::AsExpression;
+use diesel::serialize::{self, Output, ToSql};
+use diesel::sql_types::Text;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+// Declares an enum of the values a catalog is known to use for a field, with an `Other`
+// fallback for anything else. Parsing is case-insensitive and the enum (de)serializes, with
+// serde and diesel alike, as the catalog's string.
+macro_rules! string_enum {
+    (
+        $(#[$meta:meta])*
+        pub enum $name:ident {
+            $($(#[$variant_meta:meta])* $variant:ident => $value:literal,)+
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, PartialEq, Eq, Hash, AsExpression, FromSqlRow)]
+        #[diesel(sql_type = Text)]
+        pub enum $name {
+            $($(#[$variant_meta])* $variant,)+
+            Other(String),
+        }
+
+        impl $name {
+            pub fn as_str(&self) -> &str {
+                match self {
+                    $($name::$variant => $value,)+
+                    $name::Other(value) => value,
+                }
+            }
+        }
+
+        impl From<&str> for $name {
+            fn from(value: &str) -> Self {
+                let value = value.trim();
+                $(if value.eq_ignore_ascii_case($value) {
+                    return $name::$variant;
+                })+
+                $name::Other(value.to_string())
+            }
+        }
+
+        impl From<String> for $name {
+            fn from(value: String) -> Self {
+                Self::from(value.as_str())
+            }
+        }
+
+        // Lets the enum be passed where the catalog's string is expected
+        impl From<$name> for String {
+            fn from(value: $name) -> Self {
+                value.as_str().to_string()
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str(self.as_str())
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(self.as_str())
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                String::deserialize(deserializer).map(Self::from)
+            }
+        }
+
+        impl<DB> ToSql<Text, DB> for $name
+        where
+            DB: Backend,
+            str: ToSql<Text, DB>,
+        {
+            fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, DB>) -> serialize::Result {
+                self.as_str().to_sql(out)
+            }
+        }
+
+        impl<DB> FromSql<Text, DB> for $name
+        where
+            DB: Backend,
+            String: FromSql<Text, DB>,
+        {
+            fn from_sql(bytes: DB::RawValue<'_>) -> deserialize::Result<Self> {
+                String::from_sql(bytes).map(Self::from)
+            }
+        }
+    };
+}
+
+string_enum! {
+    /// How a magnitude was determined, e.g. `mb` for body-wave magnitude.
+    pub enum MagnitudeType {
+        Mb => "mb",
+        MbLg => "mb_lg",
+        Ml => "ml",
+        Md => "md",
+        Ms => "ms",
+        Ms20 => "ms_20",
+        Mw => "mw",
+        Mww => "mww",
+        Mwc => "mwc",
+        Mwb => "mwb",
+        Mwr => "mwr",
+        Mh => "mh",
+        Mi => "mi",
+        Me => "me",
+        Mlg => "mlg",
+        Mlv => "mlv",
+    }
+}
+
+string_enum! {
+    /// What kind of seismic event was recorded.
+    #[derive(Default)]
+    pub enum EventType {
+        #[default]
+        Earthquake => "earthquake",
+        QuarryBlast => "quarry blast",
+        Explosion => "explosion",
+        ChemicalExplosion => "chemical explosion",
+        NuclearExplosion => "nuclear explosion",
+        MiningExplosion => "mining explosion",
+        ExperimentalExplosion => "experimental explosion",
+        RockBurst => "rock burst",
+        Landslide => "landslide",
+        IceQuake => "ice quake",
+        SonicBoom => "sonic boom",
+        InducedOrTriggered => "induced or triggered event",
+        OtherEvent => "other event",
+    }
+}
+
+string_enum! {
+    /// Whether a seismologist has reviewed the solution.
+    pub enum ReviewStatus {
+        Automatic => "automatic",
+        Reviewed => "reviewed",
+        Deleted => "deleted",
+    }
+}
+
+string_enum! {
+    /// PAGER alert level for estimated fatalities and economic losses.
+    pub enum PagerAlert {
+        Green => "green",
+        Yellow => "yellow",
+        Orange => "orange",
+        Red => "red",
+    }
+}
+
+impl MagnitudeType {
+    // Moment magnitudes, whatever the inversion they came from
+    pub fn is_moment_magnitude(&self) -> bool {
+        matches!(
+            self,
+            MagnitudeType::Mw
+                | MagnitudeType::Mww
+                | MagnitudeType::Mwc
+                | MagnitudeType::Mwb
+                | MagnitudeType::Mwr
+        )
+    }
+}
+
+impl EventType {
+    // Man-made events, which most seismicity analyses exclude
+    pub fn is_explosion(&self) -> bool {
+        matches!(
+            self,
+            EventType::QuarryBlast
+                | EventType::Explosion
+                | EventType::ChemicalExplosion
+                | EventType::NuclearExplosion
+                | EventType::MiningExplosion
+                | EventType::ExperimentalExplosion
+        )
+    }
+}
+
+impl ReviewStatus {
+    pub fn is_reviewed(&self) -> bool {
+        *self == ReviewStatus::Reviewed
+    }
+}
+
+impl PagerAlert {
+    // Orange and red alerts call for a regional or national response
+    pub fn is_significant(&self) -> bool {
+        matches!(self, PagerAlert::Orange | PagerAlert::Red)
+    }
+}
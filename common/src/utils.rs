@@ -1,5 +1,88 @@
-use chrono::Utc;
+use std::fmt;
 
-pub fn format_time(time: &chrono::DateTime<Utc>) -> String {
-    time.format("%Y-%m-%dT%H:%M:%S").to_string()
+use chrono::{DateTime, NaiveDate, NaiveDateTime, SecondsFormat, TimeZone, Utc};
+
+/// Formats `time` as ISO 8601 in its own timezone, `Z` for UTC, with milliseconds if
+/// `fractional_seconds` is set.
+pub fn format_time<Tz>(time: &DateTime<Tz>, fractional_seconds: bool) -> String
+where
+    Tz: TimeZone,
+    Tz::Offset: fmt::Display,
+{
+    let seconds = if fractional_seconds {
+        SecondsFormat::Millis
+    } else {
+        SecondsFormat::Secs
+    };
+    time.to_rfc3339_opts(seconds, true)
+}
+
+// Parses the time forms accepted by the FDSN API: a date, a datetime without timezone (UTC) or RFC 3339
+pub fn parse_time(time: &str) -> Option<DateTime<Utc>> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(time) {
+        return Some(time.with_timezone(&Utc));
+    }
+    if let Ok(time) = NaiveDateTime::parse_from_str(time, "%Y-%m-%dT%H:%M:%S%.f") {
+        return Some(time.and_utc());
+    }
+    NaiveDate::parse_from_str(time, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .map(|time| time.and_utc())
+}
+
+/// Serde support for times kept as milliseconds since the epoch, the USGS GeoJSON form.
+///
+/// Times are written as epoch milliseconds and read from either epoch milliseconds or any
+/// string [`parse_time`] accepts, such as ISO 8601.
+pub mod epoch_millis {
+    use std::fmt;
+
+    use chrono::{DateTime, Utc};
+    use serde::de::{self, Visitor};
+    use serde::{Deserializer, Serializer};
+
+    use super::parse_time;
+
+    pub fn serialize<S: Serializer>(
+        time: &DateTime<Utc>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_i64(time.timestamp_millis())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<DateTime<Utc>, D::Error> {
+        deserializer.deserialize_any(TimeVisitor)
+    }
+
+    struct TimeVisitor;
+
+    impl<'de> Visitor<'de> for TimeVisitor {
+        type Value = DateTime<Utc>;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("milliseconds since the epoch or an ISO 8601 time")
+        }
+
+        fn visit_i64<E: de::Error>(self, millis: i64) -> Result<Self::Value, E> {
+            DateTime::from_timestamp_millis(millis)
+                .ok_or_else(|| E::custom(format!("time {millis} ms is out of range")))
+        }
+
+        fn visit_u64<E: de::Error>(self, millis: u64) -> Result<Self::Value, E> {
+            let millis = i64::try_from(millis)
+                .map_err(|_| E::custom(format!("time {millis} ms is out of range")))?;
+            self.visit_i64(millis)
+        }
+
+        fn visit_f64<E: de::Error>(self, millis: f64) -> Result<Self::Value, E> {
+            self.visit_i64(millis.round() as i64)
+        }
+
+        fn visit_str<E: de::Error>(self, time: &str) -> Result<Self::Value, E> {
+            parse_time(time).ok_or_else(|| E::custom(format!("invalid time {time:?}")))
+        }
+    }
 }
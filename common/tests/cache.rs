@@ -0,0 +1,191 @@
+mod support;
+
+use std::time::Duration;
+
+use common::cache::{CacheConfig, Cached, CACHE_FORMAT_VERSION};
+use common::earthquake_event::{CountableDataSource, EarthquakeDataSource, Errors};
+use common::fetch::fetch_calendar_windowed;
+use common::query::EventQuery;
+use common::utils::parse_time;
+use support::{event_at, temp_dir, FakeSource};
+
+fn config(name: &str) -> CacheConfig {
+    CacheConfig {
+        directory: temp_dir(name),
+        ..CacheConfig::default()
+    }
+}
+
+fn events() -> FakeSource {
+    FakeSource::new(vec![
+        event_at("a", "2024-01-10T00:00:00Z"),
+        event_at("b", "2024-02-10T00:00:00Z"),
+        event_at("c", "2024-03-10T00:00:00Z"),
+    ])
+}
+
+#[tokio::test]
+async fn equal_queries_share_an_entry_however_they_were_built() {
+    let cached = Cached::new(events(), config("normalization"));
+
+    let from_strings = EventQuery::builder()
+        .time_range("2024-01-01", "2024-02-01")
+        .unwrap()
+        .min_magnitude(3.0)
+        .build()
+        .unwrap();
+    let from_times = EventQuery::builder()
+        .min_magnitude(3.0)
+        .end_time(parse_time("2024-02-01T00:00:00+00:00").unwrap())
+        .start_time(parse_time("2024-01-01T00:00:00.000").unwrap())
+        .build()
+        .unwrap();
+
+    cached.fetch_earthquake_data(&from_strings).await.unwrap();
+    let events = cached.fetch_earthquake_data(&from_times).await.unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(cached.source().fetches(), 1);
+
+    // Counts are cached apart from the events
+    cached.count_events(&from_times).await.unwrap();
+    cached.count_events(&from_strings).await.unwrap();
+    assert_eq!(cached.source().counts(), 1);
+}
+
+#[tokio::test]
+async fn settled_windows_outlive_the_ttl() {
+    let config = CacheConfig {
+        ttl: Duration::ZERO,
+        settle_time: Duration::ZERO,
+        ..config("settled")
+    };
+    let cached = Cached::new(events(), config);
+
+    // Ended before it was fetched, so never expires
+    let closed = EventQuery::builder()
+        .time_range("2024-01-01", "2024-02-01")
+        .unwrap()
+        .build()
+        .unwrap();
+    cached.fetch_earthquake_data(&closed).await.unwrap();
+    cached.fetch_earthquake_data(&closed).await.unwrap();
+    assert_eq!(cached.source().fetches(), 1);
+
+    // Still open, so it expires with the TTL
+    let open = EventQuery::builder()
+        .time_range("2024-01-01", "2999-01-01")
+        .unwrap()
+        .build()
+        .unwrap();
+    cached.fetch_earthquake_data(&open).await.unwrap();
+    cached.fetch_earthquake_data(&open).await.unwrap();
+    assert_eq!(cached.source().fetches(), 3);
+}
+
+#[tokio::test]
+async fn offline_misses_fail_without_contacting_the_source() {
+    let cached = Cached::new(events(), config("offline").offline(true));
+    let query = EventQuery::builder()
+        .time_range("2024-01-01", "2024-02-01")
+        .unwrap()
+        .build()
+        .unwrap();
+
+    let result = cached.fetch_earthquake_data(&query).await;
+    assert!(matches!(result, Err(Errors::CacheMiss(_))));
+    assert!(matches!(
+        cached.count_events(&query).await,
+        Err(Errors::CacheMiss(_))
+    ));
+    assert_eq!(cached.source().fetches(), 0);
+    assert_eq!(cached.source().counts(), 0);
+}
+
+#[tokio::test]
+async fn calendar_windows_are_found_again_offline_the_next_day() {
+    let config = config("calendar");
+    let online = Cached::new(events(), config.clone());
+    let today = EventQuery::builder()
+        .time_range("2024-01-05", "2024-03-05")
+        .unwrap()
+        .build()
+        .unwrap();
+    let events = fetch_calendar_windowed(&online, &today, 2).await.unwrap();
+    let ids: Vec<_> = events.iter().map(|event| event.id.as_str()).collect();
+    assert_eq!(ids, ["a", "b"]);
+
+    // A day later the range has moved, the monthly windows have not
+    let offline = Cached::new(FakeSource::default(), config.offline(true));
+    let tomorrow = EventQuery::builder()
+        .time_range("2024-01-06", "2024-03-16")
+        .unwrap()
+        .build()
+        .unwrap();
+    let events = fetch_calendar_windowed(&offline, &tomorrow, 2)
+        .await
+        .unwrap();
+    let ids: Vec<_> = events.iter().map(|event| event.id.as_str()).collect();
+    assert_eq!(ids, ["a", "b", "c"]);
+    assert_eq!(offline.source().fetches(), 0);
+}
+
+#[tokio::test]
+async fn entries_of_another_format_version_are_refetched() {
+    let config = config("version");
+    let directory = config.directory.clone();
+    let cached = Cached::new(events(), config);
+    let query = EventQuery::builder()
+        .time_range("2024-01-01", "2024-02-01")
+        .unwrap()
+        .build()
+        .unwrap();
+    cached.fetch_earthquake_data(&query).await.unwrap();
+
+    let entry = std::fs::read_dir(&directory)
+        .unwrap()
+        .next()
+        .unwrap()
+        .unwrap()
+        .path();
+    let mut contents: serde_json::Value =
+        serde_json::from_slice(&std::fs::read(&entry).unwrap()).unwrap();
+    assert_eq!(contents["format_version"], CACHE_FORMAT_VERSION);
+    contents["format_version"] = (CACHE_FORMAT_VERSION - 1).into();
+    std::fs::write(&entry, serde_json::to_vec(&contents).unwrap()).unwrap();
+
+    cached.fetch_earthquake_data(&query).await.unwrap();
+    assert_eq!(cached.source().fetches(), 2);
+
+    // Entries from before the format was recorded
+    let mut contents: serde_json::Value =
+        serde_json::from_slice(&std::fs::read(&entry).unwrap()).unwrap();
+    contents.as_object_mut().unwrap().remove("format_version");
+    contents["coordinates_version"] = 2.into();
+    std::fs::write(&entry, serde_json::to_vec(&contents).unwrap()).unwrap();
+
+    cached.fetch_earthquake_data(&query).await.unwrap();
+    assert_eq!(cached.source().fetches(), 3);
+}
+
+#[tokio::test]
+async fn failing_to_write_an_entry_still_returns_the_data() {
+    // A directory that cannot be created, below a regular file
+    let blocker = temp_dir("unwritable").join("file");
+    std::fs::write(&blocker, b"").unwrap();
+    let config = CacheConfig {
+        directory: blocker.join("cache"),
+        ..CacheConfig::default()
+    };
+    let cached = Cached::new(events(), config);
+    let query = EventQuery::builder()
+        .time_range("2024-01-01", "2024-02-01")
+        .unwrap()
+        .build()
+        .unwrap();
+
+    let events = cached.fetch_earthquake_data(&query).await.unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(cached.count_events(&query).await.unwrap(), 1);
+    assert_eq!(cached.source().fetches(), 1);
+    assert_eq!(cached.source().counts(), 1);
+}
@@ -0,0 +1,63 @@
+use common::earthquake_event::{parse_events, Coordinates, EarthquakeEvent};
+use common::query::Format;
+use serde_json::Value;
+
+const SAMPLE: &str = include_str!("../../process_async/sample.json");
+
+#[test]
+fn decodes_geojson_positions_as_lon_lat_depth() {
+    let events = parse_events(Format::GeoJson, SAMPLE).unwrap();
+
+    // First feature of sample.json: "coordinates": [153.9466, -4.9758, 110.18]
+    assert_eq!(
+        events[0].coordinates,
+        Coordinates {
+            lat: -4.9758,
+            lon: 153.9466,
+            depth: 110.18,
+        }
+    );
+}
+
+#[test]
+fn round_trips_sample_positions() {
+    let sample: Value = serde_json::from_str(SAMPLE).unwrap();
+    let events = parse_events(Format::GeoJson, SAMPLE).unwrap();
+    let features = sample["features"].as_array().unwrap();
+    assert_eq!(events.len(), features.len());
+
+    for (event, feature) in events.iter().zip(features) {
+        // Compare as floats, the sample writes some depths as integers
+        let position: Vec<f64> =
+            serde_json::from_value(serde_json::to_value(&event.coordinates).unwrap()).unwrap();
+        let expected: Vec<f64> =
+            serde_json::from_value(feature["geometry"]["coordinates"].clone()).unwrap();
+        assert_eq!(position, expected);
+
+        let round_tripped: EarthquakeEvent =
+            serde_json::from_str(&serde_json::to_string(event).unwrap()).unwrap();
+        assert_eq!(round_tripped.coordinates, event.coordinates);
+    }
+}
+
+#[test]
+fn swaps_back_version_1_map_form() {
+    let coordinates: Coordinates<f64> =
+        serde_json::from_str(r#"{"lat": 153.9466, "lon": -4.9758, "depth": 110.18}"#).unwrap();
+
+    assert_eq!(coordinates.lat, -4.9758);
+    assert_eq!(coordinates.lon, 153.9466);
+}
+
+#[test]
+fn rejects_out_of_range_positions() {
+    assert!(serde_json::from_str::<Coordinates<f64>>("[-4.9758, 153.9466, 110.18]").is_err());
+    assert!(serde_json::from_str::<Coordinates<f64>>("[190.0, 10.0, 5.0]").is_err());
+}
+
+#[test]
+fn defaults_the_depth_of_plain_positions() {
+    let vertex: Coordinates<f64> = serde_json::from_str("[153.9466, -4.9758]").unwrap();
+    assert_eq!(vertex.depth, 0.0);
+    assert!(serde_json::from_str::<Coordinates<f64>>(r#"{"lat": 153.9, "lon": -4.9}"#).is_err());
+}
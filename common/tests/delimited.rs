@@ -0,0 +1,92 @@
+use common::delimited::{read_csv, read_text, DelimitedError};
+use common::types::{EventType, MagnitudeType, ReviewStatus};
+use common::utils::parse_time;
+
+const CSV: &str = include_str!("fixtures/events.csv");
+const TEXT: &str = include_str!("fixtures/events.txt");
+
+#[test]
+fn reads_usgs_csv() {
+    let events = read_csv(CSV.as_bytes()).unwrap();
+    let event = &events[0];
+
+    assert_eq!(events.len(), 2);
+    assert_eq!(event.id, "us7000abcd");
+    assert_eq!(event.net.as_deref(), Some("us"));
+    assert_eq!(event.code.as_deref(), Some("7000abcd"));
+    assert_eq!(event.time, parse_time("2024-03-01T12:34:56.789Z").unwrap());
+    assert_eq!(event.updated, parse_time("2024-03-02T08:00:00Z").unwrap());
+    assert_eq!(
+        (
+            event.coordinates.lat,
+            event.coordinates.lon,
+            event.coordinates.depth
+        ),
+        (-4.9758, 153.9466, 110.18)
+    );
+    assert_eq!((event.mag, &event.mag_type), (5.1, &Some(MagnitudeType::Mww)));
+    assert_eq!(event.status, Some(ReviewStatus::Reviewed));
+    assert_eq!(event.nst, Some(112));
+
+    let quality = event.quality.as_ref().unwrap();
+    assert_eq!(quality.horizontal_uncertainty_km, Some(7.6));
+    assert_eq!(quality.mag_station_count, Some(40));
+}
+
+#[test]
+fn reads_fdsn_text_with_padded_headers() {
+    let events = read_text(TEXT.as_bytes()).unwrap();
+    let event = &events[0];
+
+    // The header starts "#EventID | Time | ..."
+    assert_eq!(events.len(), 2);
+    assert_eq!(event.id, "20240301_0000123");
+    assert_eq!(event.net.as_deref(), Some("EMSC-RTS"));
+    assert_eq!(event.time, parse_time("2024-03-01T12:34:56.7").unwrap());
+    assert_eq!(event.updated, event.time);
+    assert_eq!(
+        (
+            event.coordinates.lat,
+            event.coordinates.lon,
+            event.coordinates.depth
+        ),
+        (35.3, 25.1, 12.0)
+    );
+    assert_eq!((event.mag, &event.mag_type), (4.6, &Some(MagnitudeType::Mb)));
+    assert_eq!(event.place.as_deref(), Some("CRETE, GREECE"));
+    assert_eq!(event.event_type, EventType::Earthquake);
+}
+
+#[test]
+fn missing_magnitudes_become_nan() {
+    let csv = read_csv(CSV.as_bytes()).unwrap();
+    let text = read_text(TEXT.as_bytes()).unwrap();
+
+    assert!(csv[1].mag.is_nan());
+    assert!(text[1].mag.is_nan());
+    assert_eq!(text[1].mag_type, Some(MagnitudeType::Ml));
+}
+
+#[test]
+fn rejects_missing_depths() {
+    let text = TEXT.replace("|35.5|", "||");
+
+    assert!(matches!(
+        read_text(text.as_bytes()),
+        Err(DelimitedError::InvalidValue {
+            line: 3,
+            column: "Depth/km",
+            ..
+        })
+    ));
+}
+
+#[test]
+fn rejects_out_of_range_coordinates() {
+    let csv = CSV.replace("-4.9758,153.9466", "153.9466,-4.9758");
+
+    assert!(matches!(
+        read_csv(csv.as_bytes()),
+        Err(DelimitedError::InvalidCoordinates { line: 2, .. })
+    ));
+}
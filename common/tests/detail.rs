@@ -0,0 +1,142 @@
+mod support;
+
+use common::detail::{parse_event_detail, NodalPlane};
+use common::earthquake_event::UsgsDataSource;
+use common::retry::RetryPolicy;
+use common::types::{MagnitudeType, PagerAlert, ReviewStatus};
+use support::{response, serve};
+
+const DETAIL: &str = include_str!("fixtures/detail.geojson");
+
+#[test]
+fn parses_the_event() {
+    let detail = parse_event_detail(DETAIL).unwrap();
+
+    let event = &detail.event;
+    assert_eq!(event.id, "us6000jllz");
+    assert_eq!(event.mag, 7.8);
+    assert_eq!(event.mag_type, Some(MagnitudeType::Mww));
+    assert_eq!(event.coordinates.lat, 37.2256);
+    assert_eq!(event.coordinates.lon, 37.0143);
+    assert_eq!(event.alert, Some(PagerAlert::Red));
+}
+
+#[test]
+fn sorts_the_preferred_moment_tensor_first() {
+    let products = parse_event_detail(DETAIL).unwrap().products;
+
+    // The fixture lists the Mwc solution before the preferred Mww one
+    let codes: Vec<_> = products
+        .moment_tensor
+        .iter()
+        .map(|tensor| tensor.product.code.as_str())
+        .collect();
+    assert_eq!(codes, ["us_6000jllz_mww", "us_6000jllz_mwc"]);
+
+    let tensor = products.preferred_moment_tensor().unwrap();
+    assert_eq!(tensor.product.preferred_weight, 218);
+    assert_eq!(tensor.derived_magnitude, Some(7.8));
+    assert_eq!(tensor.derived_magnitude_type, Some(MagnitudeType::Mww));
+    assert_eq!(tensor.derived_depth_km, Some(17.5));
+    assert_eq!(tensor.scalar_moment, Some(5.729e20));
+    assert_eq!(tensor.percent_double_couple, Some(0.91));
+    assert_eq!(tensor.mrr, Some(-2.12e19));
+    assert_eq!(tensor.mtp, Some(4.28e20));
+    assert_eq!(
+        tensor.nodal_planes,
+        [
+            NodalPlane {
+                strike: 228.0,
+                dip: 89.0,
+                rake: -1.0
+            },
+            NodalPlane {
+                strike: 318.0,
+                dip: 89.0,
+                rake: -179.0
+            },
+        ]
+    );
+    assert_eq!(
+        tensor.product.contents["quakeml.xml"].content_type,
+        "application/xml"
+    );
+
+    // Only the derived magnitude is given for the other solution
+    let other = &products.moment_tensor[1];
+    assert_eq!(other.derived_magnitude_type, Some(MagnitudeType::Mwc));
+    assert_eq!(other.scalar_moment, None);
+    assert!(other.nodal_planes.is_empty());
+}
+
+#[test]
+fn parses_the_impact_products() {
+    let products = parse_event_detail(DETAIL).unwrap().products;
+
+    let pager = products.preferred_losspager().unwrap();
+    assert_eq!(pager.alert_level, Some(PagerAlert::Red));
+    assert_eq!(pager.max_mmi, Some(9.981));
+
+    assert_eq!(products.shakemap.len(), 1);
+    let shakemap = &products.shakemap[0];
+    assert_eq!(shakemap.max_mmi, Some(9.981));
+    assert_eq!(shakemap.max_pga, Some(122.9));
+    assert_eq!(shakemap.max_pgv, Some(190.6));
+    assert_eq!(shakemap.map_status.as_deref(), Some("RELEASED"));
+    assert_eq!(shakemap.version.as_deref(), Some("13"));
+
+    assert_eq!(products.dyfi.len(), 1);
+    assert_eq!(products.dyfi[0].max_mmi, Some(9.1));
+    assert_eq!(products.dyfi[0].num_responses, Some(2000));
+}
+
+#[test]
+fn parses_origins_and_keeps_other_products_untyped() {
+    let products = parse_event_detail(DETAIL).unwrap().products;
+
+    let origin = &products.origin[0];
+    assert_eq!(origin.latitude, Some(37.2256));
+    assert_eq!(origin.longitude, Some(37.0143));
+    assert_eq!(origin.depth_km, Some(10.0));
+    assert_eq!(origin.magnitude_type, Some(MagnitudeType::Mww));
+    assert_eq!(origin.review_status, Some(ReviewStatus::Reviewed));
+    assert_eq!(origin.num_phases_used, Some(160.0));
+
+    assert!(products.focal_mechanism.is_empty());
+    assert!(products.phase_data.is_empty());
+    assert_eq!(products.other.keys().collect::<Vec<_>>(), ["finite-fault"]);
+    assert_eq!(
+        products.other["finite-fault"][0].property("maximum-slip"),
+        Some("8.2")
+    );
+}
+
+#[test]
+fn events_without_products_have_none() {
+    let mut feature: serde_json::Value = serde_json::from_str(DETAIL).unwrap();
+    feature["properties"]
+        .as_object_mut()
+        .unwrap()
+        .remove("products");
+
+    let detail = parse_event_detail(&feature.to_string()).unwrap();
+    assert_eq!(detail.event.id, "us6000jllz");
+    assert!(detail.products.moment_tensor.is_empty());
+    assert!(detail.products.other.is_empty());
+}
+
+#[tokio::test]
+async fn fetches_the_detail_by_event_id() {
+    let (base_url, requests) = serve(vec![response("200 OK", &[], DETAIL)]).await;
+    let source = UsgsDataSource::builder()
+        .base_url(&base_url)
+        .retry_policy(RetryPolicy::none())
+        .build()
+        .unwrap();
+
+    let detail = source.fetch_event_detail("us6000jllz").await.unwrap();
+    assert_eq!(detail.event.id, "us6000jllz");
+    assert!(
+        requests.lock().unwrap()[0].starts_with("GET /query?eventid=us6000jllz&format=geojson ")
+    );
+}
@@ -0,0 +1,88 @@
+use common::earthquake_event::Errors;
+use common::fdsn::FdsnCapabilities;
+use common::query::{EventQuery, Format};
+
+const WADL: &str = include_str!("fixtures/application.wadl");
+
+#[test]
+fn lists_the_resources() {
+    let capabilities = FdsnCapabilities::from_wadl(WADL).unwrap();
+
+    assert_eq!(
+        capabilities.resources.iter().collect::<Vec<_>>(),
+        ["application.wadl", "catalogs", "query", "version"]
+    );
+}
+
+#[test]
+fn detects_the_query_parameters() {
+    let capabilities = FdsnCapabilities::from_wadl(WADL).unwrap();
+
+    assert_eq!(capabilities.parameters.len(), 16);
+    for parameter in [
+        "starttime",
+        "minmagnitude",
+        "maxradius",
+        "orderby",
+        "nodata",
+    ] {
+        assert!(capabilities.supports(parameter), "{parameter}");
+    }
+    for parameter in ["contributor", "eventtype", "maxradiuskm", "updatedafter"] {
+        assert!(!capabilities.supports(parameter), "{parameter}");
+    }
+}
+
+#[test]
+fn detects_the_formats() {
+    let capabilities = FdsnCapabilities::from_wadl(WADL).unwrap();
+
+    assert_eq!(
+        capabilities.formats.iter().collect::<Vec<_>>(),
+        ["text", "xml"]
+    );
+    assert!(capabilities.supports_format(Format::Text));
+    assert!(!capabilities.supports_format(Format::GeoJson));
+
+    // A service that does not enumerate its formats is taken to support them all
+    let unlisted = WADL.replace("<option value=\"xml\"", "<ignored value=\"xml\"");
+    let unlisted = unlisted.replace("<option value=\"text\"", "<ignored value=\"text\"");
+    let capabilities = FdsnCapabilities::from_wadl(&unlisted).unwrap();
+    assert!(capabilities.formats.is_empty());
+    assert!(capabilities.supports_format(Format::Csv));
+}
+
+#[test]
+fn reports_unsupported_parameters() {
+    let capabilities = FdsnCapabilities::from_wadl(WADL).unwrap();
+    let query = EventQuery::builder()
+        .time_range("2024-01-01", "2024-02-01")
+        .unwrap()
+        .min_magnitude(4.5)
+        .rectangle(30.0, 45.0, 20.0, 30.0)
+        .contributor("us")
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        capabilities.unsupported_parameters(&query.to_query_pairs()),
+        ["format=geojson", "contributor"]
+    );
+
+    let supported = EventQuery::builder()
+        .format(Format::Text)
+        .min_magnitude(4.5)
+        .build()
+        .unwrap();
+    assert!(capabilities
+        .unsupported_parameters(&supported.to_query_pairs())
+        .is_empty());
+}
+
+#[test]
+fn rejects_malformed_documents() {
+    assert!(matches!(
+        FdsnCapabilities::from_wadl("<application>"),
+        Err(Errors::Xml(_))
+    ));
+}
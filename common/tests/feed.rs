@@ -0,0 +1,97 @@
+mod support;
+
+use common::earthquake_event::{EarthquakeDataSource, Errors};
+use common::feed::{Feed, FeedDataSource, FeedMagnitude, FeedPeriod};
+use common::query::EventQuery;
+use support::{response, serve};
+
+const FEED: &str = r#"{"type":"FeatureCollection","features":[{"type":"Feature","properties":{"mag":4.2,"place":"198 km ESE of Kokopo, Papua New Guinea","time":1391209683660,"updated":1396921399000,"tz":null,"url":"https://earthquake.usgs.gov/earthquakes/eventpage/usc000mqlp","detail":"https://earthquake.usgs.gov/fdsnws/event/1/query?eventid=usc000mqlp&format=geojson","felt":null,"cdi":null,"mmi":null,"alert":null,"status":"reviewed","tsunami":0,"sig":271,"net":"us","code":"c000mqlp","ids":",usc000mqlp,","sources":",us,","types":",origin,phase-data,","nst":null,"dmin":1.94,"rms":0.61,"gap":98,"magType":"mb","type":"earthquake","title":"M 4.2 - 198 km ESE of Kokopo, Papua New Guinea"},"geometry":{"type":"Point","coordinates":[153.9466,-4.9758,110.18]},"id":"usc000mqlp"}]}"#;
+
+const ETAG: &str = "\"5f2c-62a1\"";
+const LAST_MODIFIED: &str = "Sat, 01 Feb 2014 23:10:00 GMT";
+
+fn source(base_url: &str) -> FeedDataSource {
+    FeedDataSource::builder(Feed::new(FeedMagnitude::M2_5, FeedPeriod::Hour))
+        .base_url(base_url)
+        .build()
+        .unwrap()
+}
+
+fn header<'a>(request: &'a str, name: &str) -> Option<&'a str> {
+    request.lines().find_map(|line| {
+        let (key, value) = line.split_once(':')?;
+        key.eq_ignore_ascii_case(name).then(|| value.trim())
+    })
+}
+
+#[tokio::test]
+async fn revalidates_the_feed_and_keeps_the_events_when_unchanged() {
+    let ok = response(
+        "200 OK",
+        &[("ETag", ETAG), ("Last-Modified", LAST_MODIFIED)],
+        FEED,
+    );
+    let not_modified = response("304 Not Modified", &[], "");
+    let (base_url, requests) = serve(vec![ok, not_modified]).await;
+    let source = source(&base_url);
+
+    let first = source
+        .fetch_earthquake_data(&EventQuery::default())
+        .await
+        .unwrap();
+    let second = source
+        .fetch_earthquake_data(&EventQuery::default())
+        .await
+        .unwrap();
+
+    assert_eq!(first.len(), 1);
+    assert_eq!(first[0].id, "usc000mqlp");
+    // The 304 is answered with the events of the first response
+    assert_eq!(second.len(), 1);
+    assert_eq!(second[0].id, first[0].id);
+    assert_eq!(second[0].updated, first[0].updated);
+
+    let requests = requests.lock().unwrap();
+    assert_eq!(requests.len(), 2);
+    assert!(requests[0].starts_with("GET /2.5_hour.geojson "));
+    assert_eq!(header(&requests[0], "If-None-Match"), None);
+    assert_eq!(header(&requests[0], "If-Modified-Since"), None);
+    assert_eq!(header(&requests[1], "If-None-Match"), Some(ETAG));
+    assert_eq!(
+        header(&requests[1], "If-Modified-Since"),
+        Some(LAST_MODIFIED)
+    );
+}
+
+#[tokio::test]
+async fn replaces_the_events_when_the_feed_changes() {
+    let ok = response("200 OK", &[("ETag", ETAG)], FEED);
+    let empty = response(
+        "200 OK",
+        &[("ETag", "\"0-0\"")],
+        r#"{"type":"FeatureCollection","features":[]}"#,
+    );
+    let (base_url, requests) = serve(vec![ok, empty]).await;
+    let source = source(&base_url);
+
+    let first = source.fetch_earthquake_data(&EventQuery::default()).await;
+    let second = source.fetch_earthquake_data(&EventQuery::default()).await;
+
+    assert_eq!(first.unwrap().len(), 1);
+    assert!(second.unwrap().is_empty());
+    assert_eq!(requests.lock().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn reports_failed_requests() {
+    let (base_url, _) = serve(vec![response("404 Not Found", &[], "Not Found")]).await;
+
+    let error = source(&base_url)
+        .fetch_earthquake_data(&EventQuery::default())
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(error, Errors::Status { status, .. } if status.as_u16() == 404),
+        "{error:?}"
+    );
+}
@@ -0,0 +1,176 @@
+mod support;
+
+use common::earthquake_event::{EarthquakeEvent, Errors};
+use common::fetch::{fetch_calendar_windowed, fetch_windowed};
+use common::query::{EventQuery, OrderBy, QueryError, USGS_MAX_LIMIT};
+use support::{event_at, FakeSource};
+
+fn january() -> EventQuery {
+    EventQuery::builder()
+        .time_range("2024-01-01", "2024-02-01")
+        .unwrap()
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn splits_windows_over_the_result_cap() {
+    // Two events in one window are over the cap, one is not
+    let weight = USGS_MAX_LIMIT as u64 / 2 + 1;
+    let source = FakeSource::new(vec![
+        event_at("d", "2024-01-28T00:00:00Z"),
+        event_at("a", "2024-01-02T00:00:00Z"),
+        event_at("c", "2024-01-20T00:00:00Z"),
+        event_at("b", "2024-01-09T00:00:00Z"),
+    ])
+    .weight(weight);
+
+    let events = fetch_windowed(&source, &january(), 3).await.unwrap();
+    let ids: Vec<_> = events.iter().map(|event| event.id.as_str()).collect();
+    assert_eq!(ids, ["a", "b", "c", "d"]);
+
+    // Only the windows holding an event are fetched, each under the cap and in time order
+    let fetched = source.fetched.lock().unwrap();
+    assert_eq!(fetched.len(), 4);
+    assert!(fetched
+        .windows(2)
+        .all(|pair| pair[0].end_time() < pair[1].start_time()));
+    assert_eq!(fetched[0].start_time(), january().start_time());
+    assert_eq!(fetched[3].end_time(), january().end_time());
+}
+
+#[tokio::test]
+async fn reports_windows_that_cannot_be_split() {
+    let source = FakeSource::new(vec![
+        event_at("a", "2024-01-10T00:00:00.123Z"),
+        event_at("b", "2024-01-10T00:00:00.123Z"),
+    ])
+    .weight(USGS_MAX_LIMIT as u64);
+
+    let result = fetch_windowed(&source, &january(), 2).await;
+    match result {
+        Err(Errors::WindowTooDense {
+            count,
+            start_time,
+            end_time,
+        }) => {
+            assert_eq!(count, 2 * USGS_MAX_LIMIT as u64);
+            // Split down to the millisecond both events share
+            let time = event_at("a", "2024-01-10T00:00:00.123Z").time;
+            assert_eq!((start_time, end_time), (time, time));
+        }
+        result => panic!("expected WindowTooDense, got {result:?}"),
+    }
+    assert_eq!(source.fetches(), 0);
+}
+
+#[tokio::test]
+async fn returns_events_in_time_order() {
+    // The source answers newest first, like the web service
+    let source = FakeSource::new(vec![
+        event_at("a", "2024-01-02T00:00:00Z"),
+        event_at("c", "2024-01-20T00:00:00Z"),
+        event_at("b", "2024-01-09T00:00:00Z"),
+        event_at("outside", "2024-02-09T00:00:00Z"),
+    ]);
+
+    let events = fetch_windowed(&source, &january(), 4).await.unwrap();
+    let ids: Vec<_> = events.iter().map(|event| event.id.as_str()).collect();
+    assert_eq!(ids, ["a", "b", "c"]);
+    assert_eq!((source.counts(), source.fetches()), (1, 1));
+}
+
+#[tokio::test]
+async fn skips_windows_without_events() {
+    let source = FakeSource::new(Vec::new());
+
+    let events = fetch_windowed(&source, &january(), 4).await.unwrap();
+    assert!(events.is_empty());
+    assert_eq!(source.fetches(), 0);
+}
+
+#[tokio::test]
+async fn requires_a_time_range() {
+    let source = FakeSource::new(Vec::new());
+    let query = EventQuery::builder().min_magnitude(3.0).build().unwrap();
+
+    let result = fetch_windowed(&source, &query, 4).await;
+    assert!(matches!(
+        result,
+        Err(Errors::InvalidQuery(QueryError::MissingParameter(
+            "starttime"
+        )))
+    ));
+    assert_eq!(source.counts(), 0);
+}
+
+fn with_mag(id: &str, time: &str, mag: f64) -> EarthquakeEvent {
+    EarthquakeEvent {
+        mag,
+        ..event_at(id, time)
+    }
+}
+
+// Four events, one per window once split, so paging per window would go wrong
+fn split_source() -> FakeSource {
+    FakeSource::new(vec![
+        with_mag("a", "2024-01-02T00:00:00Z", 4.0),
+        with_mag("b", "2024-01-09T00:00:00Z", 6.0),
+        with_mag("c", "2024-01-20T00:00:00Z", 5.0),
+        with_mag("d", "2024-01-28T00:00:00Z", 3.0),
+    ])
+    .weight(USGS_MAX_LIMIT as u64 / 2 + 1)
+}
+
+#[tokio::test]
+async fn orders_and_pages_the_merged_events_once() {
+    let source = split_source();
+    let query = EventQuery::builder()
+        .time_range("2024-01-01", "2024-02-01")
+        .unwrap()
+        .order_by(OrderBy::Magnitude)
+        .offset(2)
+        .limit(2)
+        .build()
+        .unwrap();
+
+    let events = fetch_windowed(&source, &query, 3).await.unwrap();
+    let ids: Vec<_> = events.iter().map(|event| event.id.as_str()).collect();
+    assert_eq!(ids, ["c", "a"]);
+
+    // No window carries the paging
+    let fetched = source.fetched.lock().unwrap();
+    assert_eq!(fetched.len(), 4);
+    for window in fetched.iter().chain(source.counted.lock().unwrap().iter()) {
+        assert_eq!(
+            (window.order_by(), window.limit(), window.offset()),
+            (None, None, None)
+        );
+    }
+}
+
+#[tokio::test]
+async fn pages_calendar_windowed_events_in_time_order() {
+    let source = FakeSource::new(vec![
+        event_at("a", "2024-01-02T00:00:00Z"),
+        event_at("b", "2024-02-09T00:00:00Z"),
+        event_at("c", "2024-03-20T00:00:00Z"),
+    ]);
+    let query = EventQuery::builder()
+        .time_range("2024-01-01", "2024-04-01")
+        .unwrap()
+        .limit(2)
+        .build()
+        .unwrap();
+
+    let events = fetch_calendar_windowed(&source, &query, 2).await.unwrap();
+    let ids: Vec<_> = events.iter().map(|event| event.id.as_str()).collect();
+    assert_eq!(ids, ["a", "b"]);
+    assert_eq!(source.fetches(), 3);
+    assert!(source
+        .fetched
+        .lock()
+        .unwrap()
+        .iter()
+        .all(|window| window.limit().is_none()));
+}
@@ -0,0 +1,70 @@
+mod support;
+
+use std::path::Path;
+
+use common::earthquake_event::{CountableDataSource, EarthquakeDataSource, Errors};
+use common::file::FileDataSource;
+use common::query::{EventQuery, OrderBy};
+use support::temp_dir;
+
+const SAMPLE: &str = "../process_async/sample.json";
+
+#[tokio::test]
+async fn reads_the_shipped_sample() {
+    let source = FileDataSource::new(SAMPLE);
+    let all = EventQuery::builder().build().unwrap();
+    let strong = EventQuery::builder()
+        .min_magnitude(6.0)
+        .order_by(OrderBy::Magnitude)
+        .build()
+        .unwrap();
+
+    assert_eq!(source.count_events(&all).await.unwrap(), 1735);
+    let events = source.fetch_earthquake_data(&strong).await.unwrap();
+    assert_eq!(
+        source.count_events(&strong).await.unwrap(),
+        events.len() as u64
+    );
+    assert_eq!(events.len(), 6);
+    assert!(events.iter().all(|event| event.mag >= 6.0));
+    assert!(events.windows(2).all(|pair| pair[0].mag >= pair[1].mag));
+}
+
+#[tokio::test]
+async fn reads_files_once() {
+    let directory = temp_dir("file-once");
+    let path = directory.join("sample.json");
+    std::fs::copy(SAMPLE, &path).unwrap();
+    let source = FileDataSource::new(&path);
+    let query = EventQuery::builder().build().unwrap();
+
+    assert_eq!(source.count_events(&query).await.unwrap(), 1735);
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(source.clone().count_events(&query).await.unwrap(), 1735);
+}
+
+#[tokio::test]
+async fn names_the_file_that_failed() {
+    let directory = temp_dir("file-malformed");
+    let path = directory.join("broken.json");
+    std::fs::write(&path, "{").unwrap();
+
+    let error = FileDataSource::new(&path)
+        .fetch_earthquake_data(&EventQuery::default())
+        .await
+        .unwrap_err();
+    assert!(matches!(&error, Errors::File { path: failed, .. } if failed == &path));
+    assert!(error.to_string().contains("broken.json"));
+}
+
+#[tokio::test]
+async fn skips_malformed_files_in_a_directory() {
+    let directory = temp_dir("file-directory");
+    std::fs::copy(SAMPLE, directory.join("a.json")).unwrap();
+    std::fs::write(directory.join("b.json"), "{").unwrap();
+    std::fs::write(directory.join("notes.md"), "not a catalog").unwrap();
+
+    let source = FileDataSource::new(Path::new(&directory));
+    let count = source.count_events(&EventQuery::default()).await.unwrap();
+    assert_eq!(count, 1735);
+}
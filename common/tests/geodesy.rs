@@ -0,0 +1,81 @@
+use common::earthquake_event::Coordinates;
+use common::geodesy::BoundingBox;
+
+fn point(lat: f64, lon: f64) -> Coordinates<f64> {
+    Coordinates {
+        lat,
+        lon,
+        depth: 0.0,
+    }
+}
+
+fn assert_close(actual: f64, expected: f64, tolerance: f64) {
+    assert!(
+        (actual - expected).abs() <= tolerance,
+        "{actual} is not within {tolerance} of {expected}"
+    );
+}
+
+#[test]
+fn measures_distances_on_the_sphere_and_the_ellipsoid() {
+    // Flinders Peak to Buninyong, Vincenty's (1975) test line
+    let flinders_peak = point(-37.951_033_416_7, 144.424_867_888_9);
+    let buninyong = point(-37.652_821_138_9, 143.926_495_527_8);
+    assert_close(
+        flinders_peak.vincenty_km(&buninyong).unwrap(),
+        54.972_271,
+        1e-6,
+    );
+    assert_close(flinders_peak.haversine_km(&buninyong), 54.972, 0.2);
+
+    assert_eq!(buninyong.vincenty_km(&buninyong), Some(0.0));
+    assert_eq!(point(0.0, 0.0).vincenty_km(&point(0.5, 179.7)), None);
+}
+
+#[test]
+fn follows_bearings_across_the_antimeridian() {
+    let start = point(0.0, 179.0);
+    assert_close(start.initial_bearing(&point(0.0, -179.0)), 90.0, 1e-9);
+
+    let destination = start.destination(90.0, 2.0 * 111.195);
+    assert_close(destination.lat, 0.0, 1e-9);
+    assert_close(destination.lon, -179.0, 1e-3);
+
+    let midpoint = start.midpoint(&point(0.0, -179.0));
+    assert_close(midpoint.lat, 0.0, 1e-9);
+    assert_close(midpoint.lon.abs(), 180.0, 1e-9);
+}
+
+#[test]
+fn builds_bounding_boxes_crossing_the_antimeridian() {
+    let around = point(0.0, 179.5).bounding_box(111.195);
+    assert!(around.crosses_antimeridian());
+    assert!(around.contains(&point(0.5, -179.8)));
+    assert!(!around.contains(&point(0.5, -178.0)));
+
+    let enclosing = BoundingBox::enclosing(&[point(10.0, 175.0), point(-5.0, -170.0)]).unwrap();
+    assert_eq!(enclosing.min_lon, 175.0);
+    assert_eq!(enclosing.max_lon, 190.0);
+    assert_eq!((enclosing.min_lat, enclosing.max_lat), (-5.0, 10.0));
+    assert_eq!(BoundingBox::enclosing(&[]), None);
+
+    let polar = point(89.5, 0.0).bounding_box(200.0);
+    assert_eq!(
+        (polar.min_lon, polar.max_lon, polar.max_lat),
+        (-180.0, 180.0, 90.0)
+    );
+}
+
+#[test]
+fn tests_points_against_geojson_polygons_with_holes() {
+    let polygon: Vec<Vec<Coordinates<f64>>> = serde_json::from_str(
+        "[[[0, 0], [10, 0], [10, 10], [0, 10], [0, 0]], [[4, 4], [6, 4], [6, 6], [4, 6], [4, 4]]]",
+    )
+    .unwrap();
+
+    assert!(point(2.0, 2.0).in_polygon(&polygon));
+    assert!(!point(5.0, 5.0).in_polygon(&polygon));
+    assert!(!point(5.0, 12.0).in_polygon(&polygon));
+    assert!(!point(5.0, 5.0).in_polygon(&[]));
+    assert!(point(2.0, 2.0).in_multi_polygon(&[Vec::new(), polygon]));
+}
@@ -0,0 +1,51 @@
+use common::earthquake_event::{parse_events, Errors};
+use common::query::Format;
+use common::types::MagnitudeType;
+use serde_json::{json, Value};
+
+const SAMPLE: &str = include_str!("../../process_async/sample.json");
+
+// The first feature of sample.json with its properties changed by `edit`
+fn collection(edit: impl Fn(&mut serde_json::Map<String, Value>)) -> String {
+    let sample: Value = serde_json::from_str(SAMPLE).unwrap();
+    let mut feature = sample["features"][0].clone();
+    edit(feature["properties"].as_object_mut().unwrap());
+    json!({ "type": "FeatureCollection", "features": [feature] }).to_string()
+}
+
+#[test]
+fn null_magnitudes_become_nan() {
+    let body = collection(|properties| {
+        properties.insert("mag".to_string(), Value::Null);
+        properties.insert("magType".to_string(), Value::Null);
+    });
+    let events = parse_events(Format::GeoJson, &body).unwrap();
+
+    assert!(events[0].mag.is_nan());
+    assert_eq!(events[0].mag_type, None);
+}
+
+#[test]
+fn missing_magnitudes_become_nan() {
+    let body = collection(|properties| {
+        properties.remove("mag");
+    });
+    let events = parse_events(Format::GeoJson, &body).unwrap();
+
+    assert!(events[0].mag.is_nan());
+    assert_eq!(events[0].mag_type, Some(MagnitudeType::Mb));
+}
+
+#[test]
+fn requires_the_depth() {
+    let sample: Value = serde_json::from_str(SAMPLE).unwrap();
+    let mut feature = sample["features"][0].clone();
+    feature["geometry"]["coordinates"] = json!([153.9466, -4.9758]);
+    let body = json!({ "type": "FeatureCollection", "features": [feature] }).to_string();
+
+    let error = parse_events(Format::GeoJson, &body).unwrap_err();
+    assert!(
+        matches!(&error, Errors::Decode { index: 0, .. }),
+        "{error:?}"
+    );
+}
@@ -0,0 +1,160 @@
+use common::earthquake_event::{parse_events, EarthquakeEvent};
+use common::homogenize::{ConversionRule, Homogenizer, Relation};
+use common::query::Format;
+use common::types::MagnitudeType;
+
+const SAMPLE: &str = include_str!("../../process_async/sample.json");
+
+fn event(mag_type: MagnitudeType, mag: f64) -> EarthquakeEvent {
+    EarthquakeEvent {
+        mag,
+        mag_type: Some(mag_type),
+        ..EarthquakeEvent::default()
+    }
+}
+
+fn proxy(mag_type: MagnitudeType, mag: f64) -> Option<f64> {
+    Homogenizer::default().proxy_mw(&event(mag_type, mag))
+}
+
+fn assert_proxy(mag_type: MagnitudeType, mag: f64, expected: f64) {
+    let actual =
+        proxy(mag_type.clone(), mag).unwrap_or_else(|| panic!("no proxy for {mag_type} {mag}"));
+    assert!(
+        (actual - expected).abs() < 1e-9,
+        "{mag_type} {mag}: {actual} != {expected}"
+    );
+}
+
+#[test]
+fn keeps_moment_magnitudes() {
+    for mag_type in [
+        MagnitudeType::Mw,
+        MagnitudeType::Mww,
+        MagnitudeType::Mwc,
+        MagnitudeType::Mwb,
+        MagnitudeType::Mwr,
+    ] {
+        assert_proxy(mag_type, 7.3, 7.3);
+    }
+}
+
+#[test]
+fn converts_mb_within_its_range() {
+    assert_proxy(MagnitudeType::Mb, 3.5, 0.85 * 3.5 + 1.03);
+    assert_proxy(MagnitudeType::Mb, 6.2, 0.85 * 6.2 + 1.03);
+    assert_eq!(proxy(MagnitudeType::Mb, 3.49), None);
+    assert_eq!(proxy(MagnitudeType::Mb, 6.21), None);
+}
+
+#[test]
+fn converts_ms_with_the_relation_for_its_range() {
+    assert_proxy(MagnitudeType::Ms, 3.0, 0.67 * 3.0 + 2.07);
+    assert_proxy(MagnitudeType::Ms, 6.1, 0.67 * 6.1 + 2.07);
+    assert_proxy(MagnitudeType::Ms, 6.2, 0.99 * 6.2 + 0.08);
+    assert_proxy(MagnitudeType::Ms, 8.2, 0.99 * 8.2 + 0.08);
+    assert_eq!(proxy(MagnitudeType::Ms, 2.99), None);
+    // Between the two relations of Scordilis (2006)
+    assert_eq!(proxy(MagnitudeType::Ms, 6.15), None);
+    assert_eq!(proxy(MagnitudeType::Ms, 8.21), None);
+}
+
+#[test]
+fn converts_ml_quadratically_within_its_range() {
+    let ml = |mag: f64| 0.0376 * mag * mag + 0.646 * mag + 0.53;
+    assert_proxy(MagnitudeType::Ml, 1.0, ml(1.0));
+    assert_proxy(MagnitudeType::Ml, 6.0, ml(6.0));
+    assert_eq!(proxy(MagnitudeType::Ml, 0.99), None);
+    assert_eq!(proxy(MagnitudeType::Ml, 6.01), None);
+}
+
+#[test]
+fn converts_ms_20_like_ms() {
+    for mag in [3.0, 6.1, 6.15, 6.2, 8.2, 8.21] {
+        assert_eq!(
+            proxy(MagnitudeType::Ms20, mag),
+            proxy(MagnitudeType::Ms, mag)
+        );
+    }
+}
+
+#[test]
+fn converts_mb_lg_within_its_range() {
+    let mb_lg = |mag: f64| 0.0933 * mag * mag + 0.24 * mag + 1.14;
+    assert_proxy(MagnitudeType::MbLg, 3.0, mb_lg(3.0));
+    assert_proxy(MagnitudeType::MbLg, 6.5, mb_lg(6.5));
+    assert_eq!(proxy(MagnitudeType::MbLg, 2.99), None);
+    assert_eq!(proxy(MagnitudeType::MbLg, 6.51), None);
+}
+
+#[test]
+fn converts_md_like_ml() {
+    for mag in [0.99, 1.0, 3.2, 6.0, 6.01] {
+        assert_eq!(proxy(MagnitudeType::Md, mag), proxy(MagnitudeType::Ml, mag));
+    }
+    assert!(proxy(MagnitudeType::Md, 3.2).is_some());
+}
+
+#[test]
+fn converts_nearly_all_of_the_sample() {
+    let mut events = parse_events(Format::GeoJson, SAMPLE).unwrap();
+    let report = Homogenizer::default().homogenize(&mut events);
+
+    // Only mb below the 3.5 the relation was derived for is left over
+    assert_eq!(report.converted + report.unconverted_count(), 1735);
+    assert_eq!(report.unconverted.keys().collect::<Vec<_>>(), ["mb"]);
+    assert!(
+        report.unconverted_count() * 100 < events.len(),
+        "{report:?}"
+    );
+}
+
+#[test]
+fn leaves_uncovered_events_without_a_proxy() {
+    assert_eq!(proxy(MagnitudeType::Mh, 2.0), None);
+    assert_eq!(proxy(MagnitudeType::Me, 4.0), None);
+    assert_eq!(proxy(MagnitudeType::from("mfa"), 4.0), None);
+    assert_eq!(proxy(MagnitudeType::Mw, f64::NAN), None);
+}
+
+#[test]
+fn prefers_network_specific_rules() {
+    let homogenizer = Homogenizer::new(vec![
+        ConversionRule::new(MagnitudeType::Ml, Relation::Identity),
+        ConversionRule::new(
+            MagnitudeType::Ml,
+            Relation::Linear {
+                slope: 1.0,
+                intercept: -0.5,
+            },
+        )
+        .network("ci"),
+    ]);
+
+    let mut local = event(MagnitudeType::Ml, 3.0);
+    local.net = Some("CI".to_string());
+    assert_eq!(homogenizer.proxy_mw(&local), Some(2.5));
+    assert_eq!(
+        homogenizer.proxy_mw(&event(MagnitudeType::Ml, 3.0)),
+        Some(3.0)
+    );
+}
+
+#[test]
+fn reports_events_it_could_not_convert() {
+    let mut events = vec![
+        event(MagnitudeType::Mw, 6.0),
+        event(MagnitudeType::Mb, 5.0),
+        event(MagnitudeType::Mh, 2.0),
+        event(MagnitudeType::Mh, 2.5),
+        event(MagnitudeType::Mb, 7.0),
+    ];
+    let report = Homogenizer::default().homogenize(&mut events);
+
+    assert_eq!(report.converted, 2);
+    assert_eq!(report.unconverted_count(), 3);
+    assert_eq!(report.unconverted["mh"], 2);
+    assert_eq!(report.unconverted["mb"], 1);
+    assert_eq!(events[2].homogenized_mag(), None);
+    assert_eq!(events[0].homogenized_mag(), Some(6.0));
+}
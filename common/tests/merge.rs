@@ -0,0 +1,149 @@
+mod support;
+
+use std::collections::BTreeMap;
+
+use chrono::Duration;
+use common::earthquake_event::EarthquakeEvent;
+use common::merge::{merge_events, MergeConfig, MergeReport};
+use support::event_at;
+
+const NOON: &str = "2024-03-01T12:00:00Z";
+
+// An event on the equator, `seconds` after noon and `lon` degrees east
+fn solution(id: &str, net: Option<&str>, seconds: i64, lon: f64) -> EarthquakeEvent {
+    let mut event = event_at(id, NOON);
+    event.time += Duration::seconds(seconds);
+    event.updated = event.time;
+    event.net = net.map(str::to_string);
+    event.coordinates.lon = lon;
+    event
+}
+
+// Ids of the solutions of each merged event, sorted
+fn groups(report: &MergeReport) -> Vec<Vec<&str>> {
+    report
+        .events
+        .iter()
+        .map(|event| {
+            let mut ids: Vec<_> = event.solutions().map(|event| event.id.as_str()).collect();
+            ids.sort();
+            ids
+        })
+        .collect()
+}
+
+#[test]
+fn time_tolerance_is_inclusive() {
+    let config = MergeConfig::default();
+    let report = merge_events(
+        vec![
+            solution("us1", Some("us"), 0, 0.0),
+            solution("em1", Some("emsc"), 16, 0.0),
+            solution("us2", Some("us"), 100, 0.0),
+            solution("em2", Some("emsc"), 117, 0.0),
+        ],
+        &config,
+    );
+
+    assert_eq!(
+        groups(&report),
+        [vec!["em1", "us1"], vec!["us2"], vec!["em2"]]
+    );
+}
+
+#[test]
+fn distance_tolerance_is_measured_on_the_sphere() {
+    // A degree of longitude on the equator is about 111.2 km
+    let config = MergeConfig::default();
+    let report = merge_events(
+        vec![
+            solution("us1", Some("us"), 0, 0.0),
+            solution("em1", Some("emsc"), 1, 0.89),
+            solution("us2", Some("us"), 100, 0.0),
+            solution("em2", Some("emsc"), 101, 0.91),
+        ],
+        &config,
+    );
+
+    assert_eq!(
+        groups(&report),
+        [vec!["em1", "us1"], vec!["us2"], vec!["em2"]]
+    );
+}
+
+#[test]
+fn joins_the_closest_group_without_a_solution_from_its_catalog() {
+    let config = MergeConfig::default();
+    let report = merge_events(
+        vec![
+            solution("us1", Some("us"), 0, 0.0),
+            solution("us2", Some("us"), 2, 0.5),
+            // Nearer in time to us1 but much nearer in space to us2
+            solution("em1", Some("emsc"), 3, 0.45),
+            // Nearer to us2 as well, which has an emsc solution already
+            solution("em2", Some("emsc"), 5, 0.5),
+            // Both have one now
+            solution("em3", Some("emsc"), 6, 0.0),
+        ],
+        &config,
+    );
+
+    assert_eq!(
+        groups(&report),
+        [vec!["em2", "us1"], vec!["em1", "us2"], vec!["em3"]]
+    );
+}
+
+#[test]
+fn prefers_by_agency_priority_then_by_update_time() {
+    let config = MergeConfig {
+        agency_priority: vec!["EMSC".to_string(), "us".to_string()],
+        ..MergeConfig::default()
+    };
+    let mut stale = solution("em1", Some("emsc"), 1, 0.0);
+    stale.updated -= Duration::days(1);
+    let mut recent = solution("ak1", Some("ak"), 2, 0.0);
+    recent.updated += Duration::days(1);
+    let report = merge_events(
+        vec![
+            solution("us1", Some("us"), 0, 0.0),
+            recent,
+            stale,
+            solution("ci1", Some("ci"), 3, 0.0),
+        ],
+        &config,
+    );
+
+    // Listed catalogs first, case-insensitively, then the unlisted ones newest first
+    let solutions: Vec<_> = report.events[0]
+        .solutions()
+        .map(|event| event.id.as_str())
+        .collect();
+    assert_eq!(solutions, ["em1", "us1", "ak1", "ci1"]);
+}
+
+#[test]
+fn reports_unmatched_events_by_catalog() {
+    // Without priorities, the later updated em1 is preferred over us1
+    let config = MergeConfig::default();
+    let report = merge_events(
+        vec![
+            solution("em2", Some("emsc"), 500, 0.0),
+            solution("us1", Some("us"), 0, 0.0),
+            solution("em1", Some("emsc"), 5, 0.1),
+            solution("x1", None, 1000, 0.0),
+            solution("us2", Some("us"), 300, 0.0),
+            solution("us3", Some("us"), 301, 0.0),
+        ],
+        &config,
+    );
+
+    let expected = BTreeMap::from([
+        ("emsc".to_string(), vec!["em2".to_string()]),
+        ("unknown".to_string(), vec!["x1".to_string()]),
+        ("us".to_string(), vec!["us2".to_string(), "us3".to_string()]),
+    ]);
+    assert_eq!(report.unmatched, expected);
+    let preferred: Vec<_> = report.preferred().map(|event| event.id.as_str()).collect();
+    assert_eq!(preferred, ["em1", "us2", "us3", "em2", "x1"]);
+}
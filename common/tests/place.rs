@@ -0,0 +1,56 @@
+use common::earthquake_event::parse_events;
+use common::place::{CompassDirection, PlaceInfo};
+use common::query::Format;
+
+const SAMPLE: &str = include_str!("../../process_async/sample.json");
+
+#[test]
+fn parses_offsets_from_a_locality() {
+    assert_eq!(
+        PlaceInfo::parse("198 km ESE of Kokopo, Papua New Guinea"),
+        Some(PlaceInfo {
+            distance_km: Some(198.0),
+            direction: Some(CompassDirection::Ese),
+            locality: "Kokopo".to_string(),
+            region: Some("Papua New Guinea".to_string()),
+        })
+    );
+
+    let geysers = PlaceInfo::parse("5km NNW of The Geysers, CA").unwrap();
+    assert_eq!(geysers.distance_km, Some(5.0));
+    assert_eq!(geysers.direction.map(|d| d.bearing()), Some(337.5));
+    assert_eq!(geysers.locality, "The Geysers");
+    assert_eq!(geysers.region.as_deref(), Some("CA"));
+}
+
+#[test]
+fn parses_places_without_an_offset() {
+    let crete = PlaceInfo::parse("CRETE, GREECE").unwrap();
+    assert_eq!((crete.distance_km, crete.direction), (None, None));
+    assert_eq!(crete.locality, "CRETE");
+    assert_eq!(crete.region.as_deref(), Some("GREECE"));
+}
+
+#[test]
+fn falls_back_to_none_for_other_forms() {
+    for place in [
+        "",
+        "south of the Fiji Islands",
+        "Central Mid-Atlantic Ridge",
+        "Fiji region",
+        "off the coast of Oregon",
+    ] {
+        assert_eq!(PlaceInfo::parse(place), None, "{place:?}");
+    }
+}
+
+#[test]
+fn tags_sample_events() {
+    let events = parse_events(Format::GeoJson, SAMPLE).unwrap();
+    for event in &events {
+        if let Some(info) = &event.place_info {
+            assert!(event.place.as_deref().unwrap().contains(&info.locality));
+        }
+    }
+    assert!(events.iter().any(|event| event.place_info.is_some()));
+}
@@ -0,0 +1,141 @@
+use common::quakeml::{parse_quakeml, QuakeMlError};
+use common::types::{EventType, MagnitudeType, ReviewStatus};
+use common::utils::parse_time;
+
+const EVENTS: &str = include_str!("fixtures/events.quakeml");
+
+#[test]
+fn uses_the_preferred_origin_and_magnitude() {
+    let events = parse_quakeml(EVENTS).unwrap();
+    let event = &events[0];
+
+    assert_eq!(event.time, parse_time("2024-03-01T12:34:56.789Z").unwrap());
+    assert_eq!(
+        (event.coordinates.lat, event.coordinates.lon),
+        (-4.8, 153.9)
+    );
+    assert_eq!(event.mag, 6.1);
+    assert_eq!(event.mag_type, Some(MagnitudeType::Mww));
+    assert_eq!(event.nst, Some(112));
+    assert_eq!(event.rms, Some(0.73));
+    assert_eq!(
+        event.place.as_deref(),
+        Some("198 km ESE of Kokopo, Papua New Guinea")
+    );
+}
+
+#[test]
+fn falls_back_to_the_first_origin_and_magnitude() {
+    let events = parse_quakeml(EVENTS).unwrap();
+    let event = &events[1];
+
+    assert_eq!(event.time, parse_time("2024-03-02T08:00:00Z").unwrap());
+    assert_eq!(event.mag, 4.2);
+    assert_eq!(event.mag_type, Some(MagnitudeType::Ml));
+}
+
+#[test]
+fn converts_meters_to_kilometers() {
+    let events = parse_quakeml(EVENTS).unwrap();
+    let quality = events[0].quality.as_ref().unwrap();
+
+    assert_eq!(events[0].coordinates.depth, 35.5);
+    assert_eq!(quality.depth_uncertainty_km, Some(1.8));
+    assert_eq!(quality.horizontal_uncertainty_km, Some(5.4));
+    assert_eq!(events[1].coordinates.depth, 12.0);
+}
+
+#[test]
+fn builds_ids_from_the_catalog_attributes_or_the_public_id() {
+    let events = parse_quakeml(EVENTS).unwrap();
+    let ids: Vec<_> = events.iter().map(|event| event.id.as_str()).collect();
+
+    assert_eq!(
+        ids,
+        [
+            "us7000abcd",
+            "11832907",
+            "smi:example/event/without-magnitude"
+        ]
+    );
+    assert_eq!(events[0].net.as_deref(), Some("us"));
+    assert_eq!(events[0].code.as_deref(), Some("7000abcd"));
+    assert_eq!(events[1].net, None);
+}
+
+#[test]
+fn skips_events_without_an_origin() {
+    let events = parse_quakeml(EVENTS).unwrap();
+
+    assert_eq!(events.len(), 3);
+    assert!(events
+        .iter()
+        .all(|event| !event.id.contains("without-origin")));
+}
+
+#[test]
+fn events_without_a_magnitude_get_nan() {
+    let events = parse_quakeml(EVENTS).unwrap();
+
+    assert!(events[2].mag.is_nan());
+    assert_eq!(events[2].event_type, EventType::QuarryBlast);
+}
+
+#[test]
+fn review_status_follows_the_evaluation_status_before_the_mode() {
+    let events = parse_quakeml(EVENTS).unwrap();
+    let statuses: Vec<_> = events.iter().map(|event| event.status.clone()).collect();
+
+    assert_eq!(
+        statuses,
+        [
+            Some(ReviewStatus::Reviewed),
+            Some(ReviewStatus::Deleted),
+            Some(ReviewStatus::Automatic),
+        ]
+    );
+}
+
+fn single_origin(latitude: &str, depth: Option<&str>) -> String {
+    let depth = depth
+        .map(|depth| format!("<depth><value>{depth}</value></depth>"))
+        .unwrap_or_default();
+    format!(
+        r#"<q:quakeml xmlns:q="http://quakeml.org/xmlns/quakeml/1.2" xmlns="http://quakeml.org/xmlns/bed/1.2">
+  <eventParameters>
+    <event publicID="smi:example/event/1">
+      <origin publicID="smi:example/origin/1">
+        <time><value>2024-03-03T00:00:00Z</value></time>
+        <latitude><value>{latitude}</value></latitude>
+        <longitude><value>-150.0</value></longitude>
+        {depth}
+      </origin>
+    </event>
+  </eventParameters>
+</q:quakeml>"#
+    )
+}
+
+#[test]
+fn requires_the_depth() {
+    assert!(parse_quakeml(&single_origin("61.2", Some("5000"))).is_ok());
+    assert!(matches!(
+        parse_quakeml(&single_origin("61.2", None)),
+        Err(QuakeMlError::MissingElement {
+            element: "depth",
+            ..
+        })
+    ));
+}
+
+#[test]
+fn rejects_out_of_range_coordinates() {
+    let result = parse_quakeml(&single_origin("91.0", Some("5000")));
+    match result {
+        Err(QuakeMlError::InvalidCoordinates { event, reason }) => {
+            assert_eq!(event, "smi:example/event/1");
+            assert!(reason.contains("latitude 91"), "{reason}");
+        }
+        result => panic!("expected InvalidCoordinates, got {result:?}"),
+    }
+}
@@ -0,0 +1,304 @@
+mod support;
+
+use common::earthquake_event::EarthquakeEvent;
+use common::query::{EventQuery, Format, OrderBy, QueryError, Region, USGS_MAX_LIMIT};
+use common::types::EventType;
+use support::event_at;
+
+fn event(id: &str, time: &str, mag: f64) -> EarthquakeEvent {
+    EarthquakeEvent {
+        mag,
+        ..event_at(id, time)
+    }
+}
+
+fn ids(events: &[EarthquakeEvent]) -> Vec<&str> {
+    events.iter().map(|event| event.id.as_str()).collect()
+}
+
+#[test]
+fn rejects_inverted_ranges() {
+    let inverted = |lower, upper| Err(QueryError::InvertedRange { lower, upper });
+
+    assert_eq!(
+        EventQuery::builder()
+            .time_range("2024-02-01", "2024-01-01")
+            .unwrap()
+            .build(),
+        inverted("starttime", "endtime")
+    );
+    assert_eq!(
+        EventQuery::builder()
+            .time_range("2024-01-01", "2024-01-01")
+            .unwrap()
+            .build(),
+        inverted("starttime", "endtime")
+    );
+    assert_eq!(
+        EventQuery::builder()
+            .min_magnitude(5.0)
+            .max_magnitude(4.0)
+            .build(),
+        inverted("minmagnitude", "maxmagnitude")
+    );
+    assert_eq!(
+        EventQuery::builder()
+            .min_depth(50.0)
+            .max_depth(10.0)
+            .build(),
+        inverted("mindepth", "maxdepth")
+    );
+    assert_eq!(
+        EventQuery::builder()
+            .rectangle(10.0, -10.0, 0.0, 10.0)
+            .build(),
+        inverted("minlatitude", "maxlatitude")
+    );
+}
+
+#[test]
+fn rejects_out_of_range_values() {
+    let out_of_range = |query: Result<EventQuery, QueryError>| match query {
+        Err(QueryError::OutOfRange { name, .. }) => name,
+        query => panic!("expected an out of range value, got {query:?}"),
+    };
+
+    assert_eq!(
+        out_of_range(EventQuery::builder().min_magnitude(f64::NAN).build()),
+        "minmagnitude"
+    );
+    assert_eq!(
+        out_of_range(EventQuery::builder().max_magnitude(f64::INFINITY).build()),
+        "maxmagnitude"
+    );
+    assert_eq!(
+        out_of_range(EventQuery::builder().max_depth(1000.5).build()),
+        "maxdepth"
+    );
+    assert_eq!(
+        out_of_range(EventQuery::builder().circle(91.0, 0.0, 100.0).build()),
+        "latitude"
+    );
+    assert_eq!(
+        out_of_range(EventQuery::builder().limit(USGS_MAX_LIMIT + 1).build()),
+        "limit"
+    );
+    assert_eq!(
+        out_of_range(EventQuery::builder().offset(0).build()),
+        "offset"
+    );
+
+    // Bounds are inclusive and negative magnitudes are allowed
+    assert!(EventQuery::builder()
+        .min_magnitude(-1.5)
+        .max_depth(1000.0)
+        .limit(USGS_MAX_LIMIT)
+        .build()
+        .is_ok());
+}
+
+#[test]
+fn rejects_empty_and_unparsable_parameters() {
+    assert_eq!(
+        EventQuery::builder().catalog(" ").build(),
+        Err(QueryError::EmptyParameter("catalog"))
+    );
+    assert_eq!(
+        EventQuery::builder().event_type(" ").build(),
+        Err(QueryError::EmptyParameter("eventtype"))
+    );
+    assert_eq!(
+        EventQuery::builder()
+            .time_range("yesterday", "2024-01-01")
+            .map(|_| ()),
+        Err(QueryError::InvalidTime("yesterday".to_string()))
+    );
+}
+
+#[test]
+fn serializes_parameters_in_a_stable_order() {
+    let query = EventQuery::builder()
+        .contributor("us")
+        .limit(100)
+        .order_by(OrderBy::MagnitudeAsc)
+        .circle(35.0, -118.5, 250.0)
+        .min_magnitude(2.5)
+        .time_range("2024-01-01", "2024-01-02T12:30:00.25Z")
+        .unwrap()
+        .format(Format::Csv)
+        .build()
+        .unwrap();
+
+    let expected = [
+        ("format", "csv"),
+        ("starttime", "2024-01-01T00:00:00.000"),
+        ("endtime", "2024-01-02T12:30:00.250"),
+        ("minmagnitude", "2.5"),
+        ("latitude", "35"),
+        ("longitude", "-118.5"),
+        ("maxradiuskm", "250"),
+        ("orderby", "magnitude-asc"),
+        ("limit", "100"),
+        ("contributor", "us"),
+    ]
+    .map(|(name, value)| (name, value.to_string()));
+    assert_eq!(query.to_query_pairs(), expected);
+
+    let names: Vec<&str> = query
+        .to_count_pairs()
+        .into_iter()
+        .map(|(name, _)| name)
+        .collect();
+    assert_eq!(
+        names,
+        [
+            "starttime",
+            "endtime",
+            "minmagnitude",
+            "latitude",
+            "longitude",
+            "maxradiuskm",
+            "contributor"
+        ]
+    );
+}
+
+#[test]
+fn matches_inclusive_bounds_and_filters() {
+    let query = EventQuery::builder()
+        .time_range("2024-01-01", "2024-01-31")
+        .unwrap()
+        .min_magnitude(4.0)
+        .max_magnitude(6.0)
+        .event_type(EventType::Earthquake)
+        .catalog("us")
+        .build()
+        .unwrap();
+    let matching = EarthquakeEvent {
+        event_type: EventType::Earthquake,
+        net: Some("us".to_string()),
+        ..event("a", "2024-01-01T00:00:00Z", 4.0)
+    };
+
+    assert!(query.matches(&matching));
+    assert!(query.matches(&EarthquakeEvent {
+        time: matching.time + chrono::Duration::days(30),
+        mag: 6.0,
+        ..matching.clone()
+    }));
+    assert!(!query.matches(&EarthquakeEvent {
+        time: matching.time - chrono::Duration::milliseconds(1),
+        ..matching.clone()
+    }));
+    assert!(!query.matches(&EarthquakeEvent {
+        mag: f64::NAN,
+        ..matching.clone()
+    }));
+    assert!(!query.matches(&EarthquakeEvent {
+        event_type: EventType::QuarryBlast,
+        ..matching.clone()
+    }));
+    assert!(!query.matches(&EarthquakeEvent {
+        net: Some("ci".to_string()),
+        ..matching.clone()
+    }));
+}
+
+#[test]
+fn takes_event_types_by_name() {
+    let query = EventQuery::builder()
+        .event_type("Quarry Blast")
+        .build()
+        .unwrap();
+    assert_eq!(query.event_type(), Some(&EventType::QuarryBlast));
+    assert_eq!(
+        query.to_query_pairs(),
+        [
+            ("format", "geojson".to_string()),
+            ("eventtype", "quarry blast".to_string())
+        ]
+    );
+
+    // Types the enum does not know are passed on as given
+    let query = EventQuery::builder()
+        .event_type("volcanic eruption")
+        .build()
+        .unwrap();
+    let eruption = EarthquakeEvent {
+        event_type: EventType::Other("Volcanic Eruption".to_string()),
+        ..event("a", "2024-01-01T00:00:00Z", 4.0)
+    };
+    assert!(query.matches(&eruption));
+    assert!(!query.matches(&event("b", "2024-01-01T00:00:00Z", 4.0)));
+}
+
+#[test]
+fn matches_regions() {
+    let mut event = event("a", "2024-01-01T00:00:00Z", 4.0);
+    event.coordinates.lat = 0.0;
+    event.coordinates.lon = 179.5;
+
+    // Rectangles may cross the antimeridian
+    let across = Region::Rectangle {
+        min_latitude: -1.0,
+        max_latitude: 1.0,
+        min_longitude: 170.0,
+        max_longitude: 190.0,
+    };
+    assert!(EventQuery::builder()
+        .region(across)
+        .build()
+        .unwrap()
+        .matches(&event));
+
+    // Half a degree of longitude on the equator is about 55.6 km
+    let near = EventQuery::builder()
+        .circle(0.0, 180.0, 60.0)
+        .build()
+        .unwrap();
+    let far = EventQuery::builder()
+        .circle(0.0, 180.0, 50.0)
+        .build()
+        .unwrap();
+    assert!(near.matches(&event));
+    assert!(!far.matches(&event));
+}
+
+#[test]
+fn selects_newest_first_by_default() {
+    let events = vec![
+        event("old", "2024-01-01T00:00:00Z", 5.0),
+        event("new", "2024-01-03T00:00:00Z", 3.0),
+        event("mid", "2024-01-02T00:00:00Z", 7.0),
+        event("small", "2024-01-02T00:00:00Z", 1.0),
+    ];
+    let query = EventQuery::builder().min_magnitude(2.0).build().unwrap();
+
+    assert_eq!(ids(&query.select(events)), ["new", "mid", "old"]);
+}
+
+#[test]
+fn selects_in_order_and_pages_from_one() {
+    let events = vec![
+        event("a", "2024-01-01T00:00:00Z", 5.0),
+        event("b", "2024-01-02T00:00:00Z", 3.0),
+        event("c", "2024-01-03T00:00:00Z", 7.0),
+        event("d", "2024-01-04T00:00:00Z", 4.0),
+    ];
+    let by_magnitude = EventQuery::builder()
+        .order_by(OrderBy::Magnitude)
+        .build()
+        .unwrap();
+    let second_page = EventQuery::builder()
+        .order_by(OrderBy::TimeAsc)
+        .offset(3)
+        .limit(2)
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        ids(&by_magnitude.select(events.clone())),
+        ["c", "a", "d", "b"]
+    );
+    assert_eq!(ids(&second_page.select(events)), ["c", "d"]);
+}
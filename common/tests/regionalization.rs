@@ -0,0 +1,45 @@
+use common::earthquake_event::{parse_events, Coordinates};
+use common::query::Format;
+use common::regionalization::FlinnEngdahlRegion;
+
+const SAMPLE: &str = include_str!("../../process_async/sample.json");
+
+fn lookup(lat: f64, lon: f64) -> Option<FlinnEngdahlRegion> {
+    FlinnEngdahlRegion::lookup(&Coordinates {
+        lat,
+        lon,
+        depth: 10.0,
+    })
+}
+
+#[test]
+fn looks_up_geographic_and_seismic_regions() {
+    let berkeley = lookup(37.871593, -122.272743).unwrap();
+    assert_eq!(berkeley.geographic_number, 39);
+    assert_eq!(berkeley.geographic_name, "CENTRAL CALIFORNIA");
+    assert_eq!(berkeley.seismic_number, 3);
+    assert_eq!(berkeley.seismic_name, "CALIFORNIA-NEVADA REGION");
+
+    let south_island = lookup(-42.448299, 171.214005).unwrap();
+    assert_eq!(south_island.geographic_name, "SOUTH ISLAND, NEW ZEALAND");
+    assert_eq!(south_island.seismic_name, "NEW ZEALAND REGION");
+
+    let antarctica = lookup(-90.0, 0.0).unwrap();
+    assert_eq!(
+        (antarctica.geographic_number, antarctica.seismic_number),
+        (729, 50)
+    );
+
+    assert_eq!(lookup(f64::NAN, 0.0), None);
+    assert_eq!(lookup(0.0, 400.0), None);
+}
+
+#[test]
+fn tags_every_sample_event() {
+    let events = parse_events(Format::GeoJson, SAMPLE).unwrap();
+    assert!(events.iter().all(|event| event.region.is_some()));
+
+    // First feature of sample.json: 198 km ESE of Kokopo, Papua New Guinea
+    let region = events[0].region.as_ref().unwrap();
+    assert_eq!(region.seismic_name, "BISMARCK AND SOLOMON ISLANDS");
+}
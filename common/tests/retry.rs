@@ -0,0 +1,152 @@
+mod support;
+
+use std::time::{Duration, Instant};
+
+use common::earthquake_event::{CountableDataSource, Errors, UsgsDataSource};
+use common::query::EventQuery;
+use common::retry::RetryPolicy;
+use support::serve;
+
+const COUNT: &str = "HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\n42";
+
+fn unavailable(retry_after: Option<&str>) -> String {
+    let retry_after = retry_after
+        .map(|value| format!("Retry-After: {value}\r\n"))
+        .unwrap_or_default();
+    format!("HTTP/1.1 503 Service Unavailable\r\n{retry_after}Content-Length: 0\r\nConnection: close\r\n\r\n")
+}
+
+fn source(base_url: &str, retry_policy: RetryPolicy) -> UsgsDataSource {
+    UsgsDataSource::builder()
+        .base_url(base_url)
+        .retry_policy(retry_policy)
+        .build()
+        .unwrap()
+}
+
+fn quick() -> RetryPolicy {
+    RetryPolicy::default()
+        .initial_backoff(Duration::from_millis(1))
+        .jitter(false)
+}
+
+#[tokio::test]
+async fn retries_bodies_that_break_off() {
+    let truncated = "HTTP/1.1 200 OK\r\nContent-Length: 100\r\nConnection: close\r\n\r\n42";
+    let (base_url, requests) = serve(vec![truncated.to_string(), COUNT.to_string()]).await;
+
+    let count = source(&base_url, quick())
+        .count_events(&EventQuery::default())
+        .await
+        .unwrap();
+    assert_eq!(count, 42);
+    assert_eq!(requests.lock().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn waits_the_retry_after_seconds() {
+    let (base_url, requests) = serve(vec![unavailable(Some("1")), COUNT.to_string()]).await;
+    let started = Instant::now();
+
+    let count = source(&base_url, quick())
+        .count_events(&EventQuery::default())
+        .await
+        .unwrap();
+    assert_eq!(count, 42);
+    assert_eq!(requests.lock().unwrap().len(), 2);
+    assert!(started.elapsed() >= Duration::from_secs(1));
+}
+
+#[tokio::test]
+async fn waits_until_the_retry_after_date() {
+    let date = (chrono::Utc::now() + chrono::Duration::seconds(2))
+        .format("%a, %d %b %Y %H:%M:%S GMT")
+        .to_string();
+    let (base_url, requests) = serve(vec![unavailable(Some(&date)), COUNT.to_string()]).await;
+    let started = Instant::now();
+
+    source(&base_url, quick())
+        .count_events(&EventQuery::default())
+        .await
+        .unwrap();
+    assert_eq!(requests.lock().unwrap().len(), 2);
+    // The date is given to the second
+    assert!(started.elapsed() >= Duration::from_secs(1));
+}
+
+#[tokio::test]
+async fn gives_up_when_retry_after_exceeds_the_max_backoff() {
+    let (base_url, requests) = serve(vec![unavailable(Some("120")), COUNT.to_string()]).await;
+    let policy = quick().max_backoff(Duration::from_secs(1));
+
+    let result = source(&base_url, policy)
+        .count_events(&EventQuery::default())
+        .await;
+    assert!(matches!(result, Err(Errors::Status { status, .. }) if status == 503));
+    assert_eq!(requests.lock().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn caps_the_backoff_and_the_attempts() {
+    let (base_url, requests) = serve(vec![unavailable(None)]).await;
+    // Uncapped, the delays would be 100, 200 and 400 ms
+    let policy = quick()
+        .max_attempts(4)
+        .initial_backoff(Duration::from_millis(100))
+        .max_backoff(Duration::from_millis(100));
+    let started = Instant::now();
+
+    let result = source(&base_url, policy)
+        .count_events(&EventQuery::default())
+        .await;
+    let elapsed = started.elapsed();
+    assert!(matches!(result, Err(Errors::Status { status, .. }) if status == 503));
+    assert_eq!(requests.lock().unwrap().len(), 4);
+    assert!(elapsed >= Duration::from_millis(300), "{elapsed:?}");
+    assert!(elapsed < Duration::from_millis(650), "{elapsed:?}");
+}
+
+#[tokio::test]
+async fn does_not_retry_client_errors() {
+    let not_found = "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n";
+    let (base_url, requests) = serve(vec![not_found.to_string()]).await;
+
+    let result = source(&base_url, quick())
+        .count_events(&EventQuery::default())
+        .await;
+    assert!(matches!(result, Err(Errors::Status { status, .. }) if status == 404));
+    assert_eq!(requests.lock().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn does_not_retry_server_errors_that_would_repeat() {
+    for status in ["501 Not Implemented", "505 HTTP Version Not Supported"] {
+        let failed = format!("HTTP/1.1 {status}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n");
+        let (base_url, requests) = serve(vec![failed, COUNT.to_string()]).await;
+
+        let error = source(&base_url, quick())
+            .count_events(&EventQuery::default())
+            .await
+            .unwrap_err();
+        assert!(!error.is_transient(), "{error:?}");
+        assert_eq!(requests.lock().unwrap().len(), 1);
+    }
+}
+
+#[test]
+fn transient_statuses_are_the_retried_ones() {
+    let transient = |status: u16| {
+        Errors::Status {
+            status: reqwest::StatusCode::from_u16(status).unwrap(),
+            body: String::new(),
+        }
+        .is_transient()
+    };
+    for status in [429, 500, 502, 503, 504] {
+        assert!(transient(status), "{status}");
+    }
+    for status in [400, 404, 413, 501, 505] {
+        assert!(!transient(status), "{status}");
+    }
+    assert!(!Errors::NoData.is_transient());
+}
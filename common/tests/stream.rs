@@ -0,0 +1,47 @@
+use common::earthquake_event::{parse_events, Errors};
+use common::query::Format;
+use common::stream::decode_geojson;
+use futures::executor::block_on;
+use futures::TryStreamExt;
+
+const SAMPLE: &str = include_str!("../../process_async/sample.json");
+
+fn chunks(body: &str, size: usize) -> Vec<Result<Vec<u8>, Errors>> {
+    body.as_bytes()
+        .chunks(size)
+        .map(|chunk| Ok(chunk.to_vec()))
+        .collect()
+}
+
+#[test]
+fn streams_the_same_events_as_the_buffered_decoder() {
+    let expected = parse_events(Format::GeoJson, SAMPLE).unwrap();
+
+    // Chunk sizes that split keys, strings and escapes at different places
+    for size in [1, 7, 64, 4096] {
+        let events: Vec<_> =
+            block_on(decode_geojson(futures::stream::iter(chunks(SAMPLE, size))).try_collect())
+                .unwrap();
+        assert_eq!(events.len(), expected.len());
+        for (event, expected) in events.iter().zip(&expected) {
+            assert_eq!(event.id, expected.id);
+            assert_eq!(event.coordinates, expected.coordinates);
+        }
+    }
+}
+
+#[test]
+fn fails_on_a_truncated_body() {
+    let truncated = &SAMPLE[..SAMPLE.len() / 2];
+    let result: Result<Vec<_>, _> =
+        block_on(decode_geojson(futures::stream::iter(chunks(truncated, 256))).try_collect());
+    assert!(matches!(result, Err(Errors::GeoJson(_))));
+}
+
+#[test]
+fn ignores_other_top_level_members() {
+    let body = r#"{"type":"FeatureCollection","metadata":{"title":"features [x]","count":0},"bbox":[1,2],"features":[]}"#;
+    let events: Vec<_> =
+        block_on(decode_geojson(futures::stream::iter(chunks(body, 5))).try_collect()).unwrap();
+    assert!(events.is_empty());
+}
@@ -0,0 +1,140 @@
+// Shared by several test files, each of which uses only part of it
+#![allow(dead_code)]
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use common::earthquake_event::{
+    CountableDataSource, EarthquakeDataSource, EarthquakeEvent, Errors,
+};
+use common::query::EventQuery;
+use common::utils::parse_time;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+pub fn event_at(id: &str, time: &str) -> EarthquakeEvent {
+    let time = parse_time(time).unwrap();
+    EarthquakeEvent {
+        id: id.to_string(),
+        mag: 4.0,
+        time,
+        updated: time,
+        ..EarthquakeEvent::default()
+    }
+}
+
+/// Answers queries from a fixed list of events and records what it was asked.
+#[derive(Debug, Default)]
+pub struct FakeSource {
+    events: Vec<EarthquakeEvent>,
+    // How many events each matching event counts as, to reach the result cap cheaply
+    weight: u64,
+    pub fetched: Mutex<Vec<EventQuery>>,
+    pub counted: Mutex<Vec<EventQuery>>,
+    fetches: AtomicUsize,
+    counts: AtomicUsize,
+}
+
+impl FakeSource {
+    pub fn new(events: Vec<EarthquakeEvent>) -> Self {
+        Self {
+            events,
+            weight: 1,
+            ..Self::default()
+        }
+    }
+
+    pub fn weight(mut self, weight: u64) -> Self {
+        self.weight = weight;
+        self
+    }
+
+    pub fn fetches(&self) -> usize {
+        self.fetches.load(Ordering::SeqCst)
+    }
+
+    pub fn counts(&self) -> usize {
+        self.counts.load(Ordering::SeqCst)
+    }
+}
+
+#[async_trait]
+impl EarthquakeDataSource for FakeSource {
+    type Error = Errors;
+
+    async fn fetch_earthquake_data(
+        &self,
+        query: &EventQuery,
+    ) -> Result<Vec<EarthquakeEvent>, Errors> {
+        self.fetches.fetch_add(1, Ordering::SeqCst);
+        self.fetched.lock().unwrap().push(query.clone());
+        Ok(query.select(self.events.clone()))
+    }
+}
+
+#[async_trait]
+impl CountableDataSource for FakeSource {
+    async fn count_events(&self, query: &EventQuery) -> Result<u64, Errors> {
+        self.counts.fetch_add(1, Ordering::SeqCst);
+        self.counted.lock().unwrap().push(query.clone());
+        let matching = self
+            .events
+            .iter()
+            .filter(|event| query.matches(event))
+            .count();
+        Ok(matching as u64 * self.weight)
+    }
+}
+
+/// An empty directory unique to `name` under the system temporary directory.
+pub fn temp_dir(name: &str) -> std::path::PathBuf {
+    let directory = std::env::temp_dir().join(format!("common-{}-{name}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&directory);
+    std::fs::create_dir_all(&directory).unwrap();
+    directory
+}
+
+/// A `Connection: close` HTTP response with the given status line, extra headers and body.
+pub fn response(status: &str, headers: &[(&str, &str)], body: &str) -> String {
+    let headers = headers
+        .iter()
+        .map(|(name, value)| format!("{name}: {value}\r\n"))
+        .collect::<Vec<_>>()
+        .concat();
+    format!(
+        "HTTP/1.1 {status}\r\n{headers}Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )
+}
+
+/// Serves the responses on a loopback port in turn, repeating the last, and records the head of
+/// every request. Returns the base URL and the recorded requests.
+pub async fn serve(responses: Vec<String>) -> (String, Arc<Mutex<Vec<String>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base_url = format!("http://{}/", listener.local_addr().unwrap());
+    let requests = Arc::new(Mutex::new(Vec::new()));
+
+    let served = requests.clone();
+    tokio::spawn(async move {
+        loop {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buffer = [0; 1024];
+            while !request.ends_with(b"\r\n\r\n") {
+                let read = stream.read(&mut buffer).await.unwrap();
+                request.extend_from_slice(&buffer[..read]);
+            }
+            let index = {
+                let mut served = served.lock().unwrap();
+                served.push(String::from_utf8_lossy(&request).into_owned());
+                served.len() - 1
+            };
+            let response = &responses[index.min(responses.len() - 1)];
+            stream.write_all(response.as_bytes()).await.unwrap();
+            stream.shutdown().await.unwrap();
+        }
+    });
+
+    (base_url, requests)
+}
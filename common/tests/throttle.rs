@@ -0,0 +1,86 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use common::throttle::{Throttle, ThrottleConfig};
+use futures::future::join_all;
+use tokio::time::Instant;
+
+#[tokio::test(start_paused = true)]
+async fn spaces_requests_by_the_rate() {
+    let throttle = Throttle::new(&ThrottleConfig {
+        max_in_flight: 10,
+        requests_per_second: 10.0,
+    });
+    let started = Instant::now();
+
+    let acquired_at = join_all((0..5).map(|_| async {
+        let _permit = throttle.acquire().await;
+        started.elapsed()
+    }))
+    .await;
+
+    let expected: Vec<_> = (0..5).map(|n| Duration::from_millis(100 * n)).collect();
+    assert_eq!(acquired_at, expected);
+}
+
+#[tokio::test(start_paused = true)]
+async fn holds_the_in_flight_limit() {
+    let throttle = Throttle::new(&ThrottleConfig {
+        max_in_flight: 2,
+        requests_per_second: 1000.0,
+    });
+    let (in_flight, most_in_flight) = (AtomicUsize::new(0), AtomicUsize::new(0));
+
+    join_all((0..6).map(|_| async {
+        let _permit = throttle.acquire().await;
+        let now_in_flight = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+        most_in_flight.fetch_max(now_in_flight, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        in_flight.fetch_sub(1, Ordering::SeqCst);
+    }))
+    .await;
+
+    assert_eq!(most_in_flight.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test(start_paused = true)]
+async fn treats_invalid_rates_as_unlimited() {
+    for requests_per_second in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+        let throttle = Throttle::new(&ThrottleConfig {
+            max_in_flight: 1,
+            requests_per_second,
+        });
+        let started = Instant::now();
+        for _ in 0..3 {
+            drop(throttle.acquire().await);
+        }
+        assert_eq!(started.elapsed(), Duration::ZERO);
+    }
+}
+
+// The only test that touches these variables, so it cannot race with another
+#[test]
+fn reads_the_config_from_the_environment() {
+    let read = |max_in_flight: &str, requests_per_second: &str| {
+        std::env::set_var("EARTHQUAKE_MAX_IN_FLIGHT", max_in_flight);
+        std::env::set_var("EARTHQUAKE_REQUESTS_PER_SECOND", requests_per_second);
+        ThrottleConfig::from_env()
+    };
+    let default = ThrottleConfig::default();
+
+    assert_eq!(
+        read("8", "2.5"),
+        ThrottleConfig {
+            max_in_flight: 8,
+            requests_per_second: 2.5,
+        }
+    );
+    // Zero or negative rates turn the limit off
+    assert_eq!(read("8", "0").requests_per_second, 0.0);
+    assert_eq!(read("8", "-1").requests_per_second, -1.0);
+    assert_eq!(read("0", "fast"), default);
+
+    std::env::remove_var("EARTHQUAKE_MAX_IN_FLIGHT");
+    std::env::remove_var("EARTHQUAKE_REQUESTS_PER_SECOND");
+    assert_eq!(ThrottleConfig::from_env(), default);
+}
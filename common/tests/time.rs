@@ -0,0 +1,47 @@
+use chrono::{DateTime, FixedOffset, TimeZone, Utc};
+use common::utils::format_time;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Stamped {
+    #[serde(with = "common::utils::epoch_millis")]
+    time: DateTime<Utc>,
+}
+
+#[test]
+fn reads_epoch_millis_and_iso_8601_alike() {
+    let expected = Utc.timestamp_millis_opt(1_700_000_000_123).unwrap();
+    for json in [
+        r#"{"time":1700000000123}"#,
+        r#"{"time":1700000000123.0}"#,
+        r#"{"time":"2023-11-14T22:13:20.123Z"}"#,
+        r#"{"time":"2023-11-15T00:13:20.123+02:00"}"#,
+    ] {
+        let stamped: Stamped = serde_json::from_str(json).unwrap();
+        assert_eq!(stamped.time, expected, "{json}");
+    }
+}
+
+#[test]
+fn writes_epoch_millis_without_losing_precision() {
+    let stamped = Stamped {
+        time: Utc.timestamp_millis_opt(1_700_000_000_123).unwrap(),
+    };
+    assert_eq!(
+        serde_json::to_string(&stamped).unwrap(),
+        r#"{"time":1700000000123}"#
+    );
+}
+
+#[test]
+fn formats_in_the_time_zone_given() {
+    let time = Utc.timestamp_millis_opt(1_700_000_000_123).unwrap();
+    assert_eq!(format_time(&time, false), "2023-11-14T22:13:20Z");
+    assert_eq!(format_time(&time, true), "2023-11-14T22:13:20.123Z");
+
+    let east = FixedOffset::east_opt(2 * 3600).unwrap();
+    assert_eq!(
+        format_time(&time.with_timezone(&east), true),
+        "2023-11-15T00:13:20.123+02:00"
+    );
+}
@@ -0,0 +1,77 @@
+use common::types::{EventType, MagnitudeType, PagerAlert, ReviewStatus};
+
+#[test]
+fn parses_known_values_ignoring_case_and_whitespace() {
+    assert_eq!(MagnitudeType::from("mww"), MagnitudeType::Mww);
+    assert_eq!(MagnitudeType::from("Mww"), MagnitudeType::Mww);
+    assert_eq!(MagnitudeType::from(" MB_LG "), MagnitudeType::MbLg);
+    assert_eq!(MagnitudeType::from("Ms_20"), MagnitudeType::Ms20);
+    assert_eq!(
+        EventType::from("Quarry Blast".to_string()),
+        EventType::QuarryBlast
+    );
+    assert_eq!(ReviewStatus::from("REVIEWED"), ReviewStatus::Reviewed);
+    assert_eq!(PagerAlert::from("red"), PagerAlert::Red);
+}
+
+#[test]
+fn keeps_unknown_values_as_other() {
+    let mag_type = MagnitudeType::from(" mfa ");
+    assert_eq!(mag_type, MagnitudeType::Other("mfa".to_string()));
+    assert_eq!(mag_type.as_str(), "mfa");
+    assert_eq!(mag_type.to_string(), "mfa");
+    assert!(!mag_type.is_moment_magnitude());
+
+    assert_eq!(
+        EventType::from("volcanic eruption"),
+        EventType::Other("volcanic eruption".to_string())
+    );
+}
+
+#[test]
+fn writes_the_catalog_spelling() {
+    assert_eq!(MagnitudeType::MbLg.as_str(), "mb_lg");
+    assert_eq!(MagnitudeType::Ms20.to_string(), "ms_20");
+    assert_eq!(
+        String::from(EventType::InducedOrTriggered),
+        "induced or triggered event"
+    );
+    assert_eq!(EventType::default(), EventType::Earthquake);
+}
+
+#[test]
+fn serializes_as_the_catalog_string() {
+    assert_eq!(
+        serde_json::to_string(&MagnitudeType::Mww).unwrap(),
+        r#""mww""#
+    );
+    assert_eq!(
+        serde_json::to_string(&EventType::Other("volcanic eruption".to_string())).unwrap(),
+        r#""volcanic eruption""#
+    );
+
+    let parsed: Vec<MagnitudeType> = serde_json::from_str(r#"["Mb", "ml", "mfa"]"#).unwrap();
+    assert_eq!(
+        parsed,
+        [
+            MagnitudeType::Mb,
+            MagnitudeType::Ml,
+            MagnitudeType::Other("mfa".to_string())
+        ]
+    );
+    for status in [ReviewStatus::Automatic, ReviewStatus::Reviewed] {
+        let json = serde_json::to_string(&status).unwrap();
+        assert_eq!(serde_json::from_str::<ReviewStatus>(&json).unwrap(), status);
+    }
+    assert!(serde_json::from_str::<PagerAlert>("1").is_err());
+}
+
+#[test]
+fn classifies_values() {
+    assert!(MagnitudeType::Mwr.is_moment_magnitude());
+    assert!(!MagnitudeType::Ml.is_moment_magnitude());
+    assert!(EventType::QuarryBlast.is_explosion());
+    assert!(!EventType::Earthquake.is_explosion());
+    assert!(PagerAlert::Orange.is_significant());
+    assert!(!PagerAlert::Yellow.is_significant());
+}
@@ -0,0 +1,188 @@
+mod support;
+
+use common::earthquake_event::{CountableDataSource, EarthquakeDataSource, Errors, UsgsDataSource};
+use common::query::{EventQuery, Format};
+use common::retry::RetryPolicy;
+use support::{response, serve};
+
+const FEATURE: &str = r#"{"type":"Feature","properties":{"mag":4.2,"place":"198 km ESE of Kokopo, Papua New Guinea","time":1391209683660,"updated":1396921399000,"tz":null,"url":"https://earthquake.usgs.gov/earthquakes/eventpage/usc000mqlp","detail":"https://earthquake.usgs.gov/fdsnws/event/1/query?eventid=usc000mqlp&format=geojson","felt":null,"cdi":null,"mmi":null,"alert":null,"status":"reviewed","tsunami":0,"sig":271,"net":"us","code":"c000mqlp","ids":",usc000mqlp,","sources":",us,","types":",origin,phase-data,","nst":null,"dmin":1.94,"rms":0.61,"gap":98,"magType":"mb","type":"earthquake","title":"M 4.2 - 198 km ESE of Kokopo, Papua New Guinea"},"geometry":{"type":"Point","coordinates":[153.9466,-4.9758,110.18]},"id":"usc000mqlp"}"#;
+
+fn source(base_url: &str) -> UsgsDataSource {
+    UsgsDataSource::builder()
+        .base_url(base_url)
+        .retry_policy(RetryPolicy::none())
+        .build()
+        .unwrap()
+}
+
+fn geojson(features: &[String]) -> String {
+    format!(
+        r#"{{"type":"FeatureCollection","features":[{}]}}"#,
+        features.join(",")
+    )
+}
+
+async fn fetch(responses: Vec<String>, query: &EventQuery) -> Result<usize, Errors> {
+    let (base_url, _) = serve(responses).await;
+    let events = source(&base_url).fetch_earthquake_data(query).await?;
+    Ok(events.len())
+}
+
+#[tokio::test]
+async fn decodes_the_events_of_a_query() {
+    let body = geojson(&[FEATURE.to_string()]);
+    let (base_url, requests) = serve(vec![response("200 OK", &[], &body)]).await;
+
+    let events = source(&base_url)
+        .fetch_earthquake_data(&EventQuery::default())
+        .await
+        .unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].id, "usc000mqlp");
+    assert!(requests.lock().unwrap()[0].starts_with("GET /query?"));
+}
+
+#[tokio::test]
+async fn no_content_is_no_data() {
+    let error = fetch(
+        vec![response("204 No Content", &[], "")],
+        &EventQuery::default(),
+    )
+    .await
+    .unwrap_err();
+    assert!(matches!(error, Errors::NoData), "{error:?}");
+
+    // Whatever the format
+    let text = EventQuery::builder().format(Format::Text).build().unwrap();
+    let error = fetch(vec![response("204 No Content", &[], "")], &text)
+        .await
+        .unwrap_err();
+    assert!(matches!(error, Errors::NoData), "{error:?}");
+}
+
+#[tokio::test]
+async fn keeps_the_error_text_of_failed_requests() {
+    let body = "Error 400: Bad Request\n\nBad minmagnitude value \"big\".\n";
+    let error = fetch(
+        vec![response("400 Bad Request", &[], body)],
+        &EventQuery::default(),
+    )
+    .await
+    .unwrap_err();
+
+    match error {
+        Errors::Status { status, body } => {
+            assert_eq!(status.as_u16(), 400);
+            assert_eq!(
+                body,
+                "Error 400: Bad Request\n\nBad minmagnitude value \"big\"."
+            );
+        }
+        error => panic!("{error:?}"),
+    }
+}
+
+#[tokio::test]
+async fn names_the_feature_that_failed_to_decode() {
+    let broken = FEATURE
+        .replace("usc000mqlp", "usc000broken")
+        .replace(r#""mag":4.2"#, r#""mag":"strong""#);
+    let body = geojson(&[FEATURE.to_string(), broken]);
+
+    let error = fetch(vec![response("200 OK", &[], &body)], &EventQuery::default())
+        .await
+        .unwrap_err();
+    match error {
+        Errors::Decode { index, id, .. } => {
+            assert_eq!(index, 1);
+            assert_eq!(id.as_deref(), Some("usc000broken"));
+        }
+        error => panic!("{error:?}"),
+    }
+}
+
+const CATALOGS: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<Catalogs>
+  <Catalog>ak</Catalog>
+  <Catalog>at</Catalog>
+  <Catalog> us </Catalog>
+  <Catalog></Catalog>
+</Catalogs>"#;
+
+const CONTRIBUTORS: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<Contributors>
+  <Contributor>admin</Contributor>
+  <Contributor>ak</Contributor>
+  <Contributor>us</Contributor>
+</Contributors>"#;
+
+#[tokio::test]
+async fn counts_from_a_plain_text_body() {
+    let (base_url, requests) = serve(vec![response("200 OK", &[], "1735\n")]).await;
+    let query = EventQuery::builder()
+        .time_range("2014-01-01", "2014-02-01")
+        .unwrap()
+        .build()
+        .unwrap();
+
+    let count = source(&base_url).count_events(&query).await.unwrap();
+    assert_eq!(count, 1735);
+
+    // Without a format parameter, which would ask for JSON instead of the plain number
+    let requests = requests.lock().unwrap();
+    assert!(
+        requests[0].starts_with(
+            "GET /count?starttime=2014-01-01T00%3A00%3A00.000&endtime=2014-02-01T00%3A00%3A00.000 "
+        ),
+        "{}",
+        requests[0]
+    );
+}
+
+#[tokio::test]
+async fn rejects_count_bodies_that_are_not_a_number() {
+    let body = r#"{"count":1735,"maxAllowed":20000}"#;
+    let (base_url, _) = serve(vec![response("200 OK", &[], body)]).await;
+
+    let error = source(&base_url)
+        .count_events(&EventQuery::default())
+        .await
+        .unwrap_err();
+    match error {
+        Errors::UnexpectedCountResponse(response) => assert_eq!(response, body),
+        error => panic!("{error:?}"),
+    }
+}
+
+#[tokio::test]
+async fn lists_catalogs_and_contributors() {
+    let (base_url, requests) = serve(vec![
+        response("200 OK", &[], CATALOGS),
+        response("200 OK", &[], CONTRIBUTORS),
+    ])
+    .await;
+    let source = source(&base_url);
+
+    assert_eq!(source.catalogs().await.unwrap(), ["ak", "at", "us"]);
+    assert_eq!(source.contributors().await.unwrap(), ["admin", "ak", "us"]);
+
+    let requests = requests.lock().unwrap();
+    assert!(requests[0].starts_with("GET /catalogs "));
+    assert!(requests[1].starts_with("GET /contributors "));
+}
+
+#[tokio::test]
+async fn reads_the_service_version() {
+    let (base_url, requests) = serve(vec![response("200 OK", &[], "1.14.1\n")]).await;
+
+    assert_eq!(source(&base_url).version().await.unwrap(), "1.14.1");
+    assert!(requests.lock().unwrap()[0].starts_with("GET /version "));
+}
+
+#[tokio::test]
+async fn reports_malformed_catalog_lists() {
+    let (base_url, _) = serve(vec![response("200 OK", &[], "<Catalogs><Catalog>ak")]).await;
+
+    let error = source(&base_url).catalogs().await.unwrap_err();
+    assert!(matches!(error, Errors::Xml(_)), "{error:?}");
+}
@@ -1,46 +1,90 @@
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use common::blocking::earthquake_event::*;
-use common::utils::format_time;
+use common::earthquake_event::EarthquakeDataSource;
+use common::fdsn::FdsnDataSource;
+use common::feed::{Feed, FeedDataSource, FeedMagnitude, FeedPeriod};
+use common::query::{EventQuery, Format};
+use std::collections::HashMap;
+use std::env;
 use std::thread;
 use std::time::Duration;
 
-fn fetch_earthquake_data_real_time(
-    source: &dyn EarthquakeDataSource<Error = Errors>,
-    format: &str,
+// How far back each poll looks. Events reach the summary feeds and the FDSN services minutes
+// after their origin time and are revised later, so new and updated events are told apart by
+// id and update time rather than by origin time.
+const LOOKBACK_MINUTES: i64 = 60;
+
+fn fetch_earthquake_data_real_time<S>(
+    source: &BlockingDataSource<S>,
+    format: Format,
     polling_interval_secs: u64,
-) {
+) where
+    S: EarthquakeDataSource<Error = Errors>,
+{
+    // Origin and update time of every event printed so far, by id
+    let mut seen: HashMap<String, (DateTime<Utc>, DateTime<Utc>)> = HashMap::new();
+
     loop {
         // Calculate start and end times dynamically
         let current_time = Utc::now();
-        let end_time = format_time(&current_time);
-        let start_time =
-            format_time(&(current_time - chrono::Duration::seconds(polling_interval_secs as i64)));
-
-        let min_magnitude = "3";
+        let start_time = current_time - chrono::Duration::minutes(LOOKBACK_MINUTES);
+        let query = EventQuery::builder()
+            .format(format)
+            .start_time(start_time)
+            .end_time(current_time)
+            .min_magnitude(3.0)
+            .build();
 
         // Fetch earthquake data synchronously
-        let usgs_earthquake_data =
-            source.fetch_earthquake_data(format, &start_time, &end_time, min_magnitude);
+        let usgs_earthquake_data = query
+            .map_err(Errors::from)
+            .and_then(|query| source.fetch_earthquake_data(&query));
 
         match usgs_earthquake_data {
             Ok(earthquake_events) => {
                 for event in earthquake_events {
-                    println!("{event:?}");
+                    let is_new = seen
+                        .get(&event.id)
+                        .map_or(true, |&(_, updated)| event.updated > updated);
+                    if is_new {
+                        seen.insert(event.id.clone(), (event.time, event.updated));
+                        println!("{event:?}");
+                    }
                 }
             }
+            Err(Errors::NoData) => {}
             Err(e) => eprintln!("{e:?}"),
         }
 
+        // Events that left the lookback window will not be returned again
+        seen.retain(|_, &mut (time, _)| time >= start_time);
+
         // Pause for the polling interval before the next fetch
         thread::sleep(Duration::from_secs(polling_interval_secs));
     }
 }
 
 // Example usage
-pub fn run_fetch_sync() {
-    let usgs_data_source = UsgsDataSource;
-    let format = "geojson";
+pub fn run_fetch_sync() -> Result<(), Errors> {
     let polling_interval_secs = 60; // Fetch every 1 minute
 
-    fetch_earthquake_data_real_time(&usgs_data_source, format, polling_interval_secs);
+    // EARTHQUAKE_FDSN_URL selects another FDSN event service, e.g. common::fdsn::EMSC_BASE_URL
+    match env::var("EARTHQUAKE_FDSN_URL") {
+        Ok(base_url) => {
+            let fdsn_data_source = BlockingDataSource::new(FdsnDataSource::new(base_url)?)?;
+            fetch_earthquake_data_real_time(&fdsn_data_source, Format::Text, polling_interval_secs);
+        }
+        Err(_) => {
+            // The hourly M2.5+ summary feed covers the lookback window at minimum magnitude 3
+            let feed = Feed::new(FeedMagnitude::M2_5, FeedPeriod::Hour);
+            let feed_data_source = BlockingDataSource::new(FeedDataSource::new(feed)?)?;
+            fetch_earthquake_data_real_time(
+                &feed_data_source,
+                Format::GeoJson,
+                polling_interval_secs,
+            );
+        }
+    }
+
+    Ok(())
 }
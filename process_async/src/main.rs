@@ -3,49 +3,68 @@ pub mod statistics;
 pub mod temporal;
 
 use clustering::cluster_earthquake_events;
-use common::fetch::run_fetch;
+use common::cache::CacheConfig;
+use common::earthquake_event::{EarthquakeDataSource, EarthquakeEvent};
+use common::fetch::run_fetch_cached;
+use common::homogenize::Homogenizer;
+use common::file::FileDataSource;
+use common::query::{EventQuery, OrderBy};
 use statistics::calculate_all_cluster_statistics_async;
 use temporal::{events_to_dataframe, temporal_analysis};
+use std::collections::BTreeMap;
 use std::error::Error;
-use tokio;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
+    // Fetch politeness settings may be given in .env
+    dotenvy::dotenv().ok();
+
     // Define the start date as ten years ago from today
     let end_date = chrono::Utc::now().date_naive();
-    let start_date = end_date - chrono::Duration::days(1*365);
+    let start_date = end_date - chrono::Duration::days(365);
 
-    // Create a date iterator to fetch data monthly
-    let mut current_date = start_date.clone();
-    let mut tasks = Vec::new(); // Vector to hold asynchronous tasks
+    let start_time = start_date.to_string();
+    let end_time = end_date.to_string();
+    let min_magnitude = 3; // Set your desired minimum magnitude here
 
-    while current_date < end_date {
-        let next_2day = current_date + chrono::Duration::days(2);
-        let start_time = current_date.to_string();
-        let end_time = next_2day.to_string();
-        let min_magnitude = 3; // Set your desired minimum magnitude here
+    let args: Vec<String> = std::env::args().collect();
+    let input = args
+        .iter()
+        .position(|arg| arg == "--file")
+        .and_then(|index| args.get(index + 1));
 
-        // Clone the values to pass to the asynchronous task
-        let start_time_clone = start_time.clone();
-        let end_time_clone = end_time.clone();
+    let mut all_earthquake_events = match input {
+        // --file <path> reads a local catalog file or directory, e.g. sample.json, whatever its dates
+        Some(path) => {
+            println!("Reading data from {}", path);
+            let query = EventQuery::builder()
+                .min_magnitude(min_magnitude as f64)
+                .order_by(OrderBy::TimeAsc)
+                .build()?;
+            FileDataSource::new(path).fetch_earthquake_data(&query).await?
+        }
+        None => {
+            // Log the start and end times being fetched
+            println!("Fetching data for Start: {} End: {}", start_time, end_time);
 
-        // Log the start and end times being fetched
-        println!("Fetching data for Start: {} End: {}", start_time_clone, end_time_clone);
+            // With --offline the events are replayed from the response cache only
+            let offline = args.iter().any(|arg| arg == "--offline");
+            let cache_config = CacheConfig::from_env().offline(offline);
 
-        // Spawn asynchronous task for fetching data and push it to the vector
-        tasks.push(tokio::spawn(async move {
-            run_fetch(&start_time_clone, &end_time_clone, min_magnitude).await
-        }));
+            // The period is split into windows under the USGS result cap and fetched concurrently
+            run_fetch_cached(&start_time, &end_time, min_magnitude, cache_config).await?
+        }
+    };
 
-        // Move to the next month
-        current_date = next_2day;
-    }
-
-    let mut all_earthquake_events = Vec::new();
-    for task in tasks {
-        let earthquake_events = task.await??; // Unwrap the Result twice
-        all_earthquake_events.extend(earthquake_events);
-    }
+    // Bring mb, ml, ms and the like onto one scale before comparing magnitudes
+    let report = Homogenizer::default().homogenize(&mut all_earthquake_events);
+    // Events without a proxy moment magnitude are left out of the magnitude statistics
+    println!(
+        "Homogenized {} events, {} without a conversion: {:?}",
+        report.converted,
+        report.unconverted_count(),
+        report.unconverted
+    );
 
     // Set the number of clusters for k-means clustering
     let k = 20; // Adjust as needed
@@ -69,5 +88,23 @@ async fn main() -> Result<(), Box<dyn Error>> {
         println!("{:?}", temporal_df);
     }
 
+    // Perform temporal analysis for each Flinn-Engdahl seismic region, a standard grouping
+    // that stays the same from run to run unlike the clusters
+    let mut regions: BTreeMap<(u8, String), Vec<EarthquakeEvent>> = BTreeMap::new();
+    for event in &all_earthquake_events {
+        if let Some(region) = &event.region {
+            regions
+                .entry((region.seismic_number, region.seismic_name.clone()))
+                .or_default()
+                .push(event.clone());
+        }
+    }
+    for ((number, name), events) in regions {
+        let df = events_to_dataframe(events)?;
+        let temporal_df = temporal_analysis(&df).await?;
+        println!("Temporal Analysis for Seismic Region {} ({}):", number, name);
+        println!("{:?}", temporal_df);
+    }
+
     Ok(())
 }
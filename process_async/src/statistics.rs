@@ -1,29 +1,28 @@
 use crate::clustering::EarthquakeCluster;
-use chrono::{Duration, NaiveDateTime};
+use chrono::{DateTime, Duration, Utc};
 use std::error::Error;
 use std::fmt;
 
 // Function to calculate time since the last earthquake with magnitude greater than 5 for an individual cluster
+// Magnitudes are compared as proxy moment magnitudes, see common::homogenize; events without
+// one are left out
 pub async fn calculate_time_since_last_significant_earthquake(
     cluster: &EarthquakeCluster,
 ) -> Option<Duration> {
     // Find the most recent significant earthquake for the cluster
-    let mut most_recent_timestamp: Option<NaiveDateTime> = None;
+    let mut most_recent_timestamp: Option<DateTime<Utc>> = None;
 
     for earthquake in &cluster.events {
-        let timestamp_secs = earthquake.time / 1000;
-        let earthquake_datetime = NaiveDateTime::from_timestamp_opt(timestamp_secs, 0);
-
-        if earthquake.mag > 5.0 {
-            if most_recent_timestamp.is_none() || earthquake_datetime > most_recent_timestamp {
-                most_recent_timestamp = earthquake_datetime;
-            }
+        if earthquake.homogenized_mag().is_some_and(|mag| mag > 5.0)
+            && most_recent_timestamp.map_or(true, |timestamp| earthquake.time > timestamp)
+        {
+            most_recent_timestamp = Some(earthquake.time);
         }
     }
 
     // Calculate time since the last significant earthquake for the cluster
     if let Some(timestamp) = most_recent_timestamp {
-        let current_time = chrono::Utc::now().naive_utc();
+        let current_time = Utc::now();
         Some(current_time - timestamp)
     } else {
         None // No significant earthquake found
@@ -39,7 +38,7 @@ pub struct MetricStatistics {
 }
 
 // Function to calculate statistics for a single metric using DataFusion
-async fn calculate_metric_statistics_async(metric_data: &Vec<f64>) -> MetricStatistics {
+async fn calculate_metric_statistics_async(metric_data: &[f64]) -> MetricStatistics {
     // Filter out infinite values before calculating min and max
     let finite_data: Vec<f64> = metric_data.iter().cloned().filter(|&x| x.is_finite()).collect();
 
@@ -66,15 +65,13 @@ pub async fn calculate_cluster_statistics_async(
         .events
         .iter()
         .map(|event| event.coordinates.depth)
-        .collect::<Vec<f64>>()
-        .into();
+        .collect::<Vec<f64>>();
 
     let magnitude_data: Vec<f64> = cluster
         .events
         .iter()
-        .map(|event| event.mag)
-        .collect::<Vec<f64>>()
-        .into();
+        .filter_map(|event| event.homogenized_mag())
+        .collect::<Vec<f64>>();
 
     let (depth_stats, magnitude_stats) = tokio::join!(
         calculate_metric_statistics_async(&depth_data),
@@ -89,7 +86,7 @@ pub async fn calculate_all_cluster_statistics_async(
     clusters: Vec<EarthquakeCluster>,
 ) -> Result<Vec<ClusterStatistics>, Box<dyn Error>> {
     let tasks = clusters.into_iter().map(|cluster| {
-        let centroid = cluster.centroid.clone(); // Clone centroid
+        let centroid = cluster.centroid; // Copy centroid
         let cluster_clone = cluster.clone(); // Clone cluster for async block
 
         async move {
@@ -2,16 +2,22 @@ use polars::prelude::*;
 use common::earthquake_event::EarthquakeEvent;
 
 pub fn events_to_dataframe(events: Vec<EarthquakeEvent>) -> Result<DataFrame, PolarsError> {
-    let timestamps: Vec<i64> = events.iter().map(|e| e.time).collect();
-    let magnitudes: Vec<f64> = events.iter().map(|e| e.mag).collect();
+    let timestamps: Vec<i64> = events.iter().map(|e| e.time.timestamp_millis()).collect();
+    // Null where no proxy moment magnitude could be computed
+    let magnitudes: Vec<Option<f64>> = events.iter().map(|e| e.homogenized_mag()).collect();
     let latitudes: Vec<f64> = events.iter().map(|e| e.coordinates.lat).collect();
     let longitudes: Vec<f64> = events.iter().map(|e| e.coordinates.lon).collect();
+    let seismic_regions: Vec<Option<u32>> = events
+        .iter()
+        .map(|e| e.region.as_ref().map(|region| region.seismic_number as u32))
+        .collect();
 
     let df = DataFrame::new(vec![
         Series::new("timestamp", timestamps),
         Series::new("magnitude", magnitudes),
         Series::new("latitude", latitudes),
         Series::new("longitude", longitudes),
+        Series::new("seismic_region", seismic_regions),
     ])?;
 
     Ok(df)
@@ -40,8 +46,8 @@ pub async fn temporal_analysis(df: &DataFrame) -> Result<DataFrame, PolarsError>
         ])
         .group_by([col("year"), col("month")])
         .agg([
-            // Count the number of "magnitude" entries per group
-            col("magnitude").count().alias("count"),
+            // Count the number of events per group, with or without a magnitude
+            col("timestamp").count().alias("count"),
         ])
         .collect()?;
 
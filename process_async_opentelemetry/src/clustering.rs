@@ -61,7 +61,7 @@ pub fn cluster_earthquake_events(
         .predict(observations)
         .targets()
         .iter()
-        .map(|&cluster_idx| cluster_idx as usize)
+        .copied()
         .collect::<Vec<usize>>();
 
     // Initialize EarthquakeCluster instances
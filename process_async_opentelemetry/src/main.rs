@@ -10,10 +10,13 @@ use tracing_subscriber::{layer::SubscriberExt, EnvFilter, Layer, Registry};
 
 use clustering::cluster_earthquake_events;
 use common::fetch::run_fetch;
+use common::homogenize::Homogenizer;
 use statistics::calculate_all_cluster_statistics_async;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
+    dotenvy::dotenv().ok();
+
     let console_env_filter = EnvFilter::builder()
         .with_env_var("RUST_LOG")
         .with_default_directive(LevelFilter::INFO.into())
@@ -69,32 +72,24 @@ async fn process(
     let end_date = end_date.date_naive();
     let start_date = end_date - back_until;
 
-    // Create a date iterator to fetch data monthly
-    let mut current_date = start_date;
-    let mut tasks = Vec::new(); // Vector to hold asynchronous tasks
-
-    while current_date < end_date {
-        let next_2day = current_date + chrono::Duration::days(2);
-        let start_time = current_date.to_string();
-        let end_time = next_2day.to_string();
-        let min_magnitude = 3; // Set your desired minimum magnitude here
+    let start_time = start_date.to_string();
+    let end_time = end_date.to_string();
+    let min_magnitude = 3; // Set your desired minimum magnitude here
 
-        tracing::info!(start_time, end_time, "Fetching data");
+    tracing::info!(start_time, end_time, "Fetching data");
 
-        // Spawn asynchronous task for fetching data and push it to the vector
-        tasks.push(tokio::spawn(async move {
-            run_fetch(&start_time, &end_time, min_magnitude).await
-        }));
+    // run_fetch counts first and splits the range so no request hits the 20,000 event cap
+    let mut all_earthquake_events = run_fetch(&start_time, &end_time, min_magnitude).await?;
 
-        // Move to the next month
-        current_date = next_2day;
-    }
-
-    let mut all_earthquake_events = Vec::new();
-    for task in tasks {
-        let earthquake_events = task.await??; // Unwrap the Result twice
-        all_earthquake_events.extend(earthquake_events);
-    }
+    // Bring mb, ml, ms and the like onto one scale before comparing magnitudes
+    let report = Homogenizer::default().homogenize(&mut all_earthquake_events);
+    // Events without a proxy moment magnitude are left out of the magnitude statistics
+    tracing::info!(
+        converted = report.converted,
+        unconverted = report.unconverted_count(),
+        by_magnitude_type = ?report.unconverted,
+        "Homogenized magnitudes"
+    );
 
     // Set the number of clusters for k-means clustering
     let k = 20; // todo, make this a constant, or function parameter
@@ -0,0 +1,2 @@
+-- Events far from any locality have no place name
+ALTER TABLE earthquake_events ALTER COLUMN place DROP NOT NULL;
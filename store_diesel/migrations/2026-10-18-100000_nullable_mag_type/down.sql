@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+UPDATE earthquake_events SET mag_type = '' WHERE mag_type IS NULL;
+ALTER TABLE earthquake_events ALTER COLUMN mag_type SET NOT NULL;
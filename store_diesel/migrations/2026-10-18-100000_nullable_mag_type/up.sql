@@ -0,0 +1,2 @@
+-- Events without a magnitude have no magnitude type
+ALTER TABLE earthquake_events ALTER COLUMN mag_type DROP NOT NULL;
@@ -2,8 +2,7 @@ pub mod models;
 pub mod schema;
 
 use self::models::EarthquakeEventModel;
-use chrono::NaiveDateTime;
-use common::blocking::earthquake_event::EarthquakeEvent;
+use common::earthquake_event::EarthquakeEvent;
 use diesel::prelude::*;
 use dotenvy::dotenv;
 use std::env;
@@ -33,17 +32,15 @@ pub fn convert_to_model(events: Vec<EarthquakeEvent>) -> Vec<EarthquakeEventMode
     events
         .into_iter()
         .map(|event| {
-            let time = NaiveDateTime::from_timestamp_opt(event.time / 1000, 0);
-            let updated = NaiveDateTime::from_timestamp_opt(event.updated / 1000, 0);
-
             EarthquakeEventModel {
                 mag: event.mag,
                 place: event.place,
-                time: time,
-                updated: updated,
+                // Timestamptz keeps the milliseconds
+                time: Some(event.time.naive_utc()),
+                updated: Some(event.updated.naive_utc()),
                 tsunami: event.tsunami,
-                lon: event.coordinates[0],
-                lat: event.coordinates[1],
+                lon: event.coordinates.lon,
+                lat: event.coordinates.lat,
                 mag_type: event.mag_type,
                 event_type: event.event_type,
             }
@@ -1,4 +1,5 @@
 use chrono::NaiveDateTime;
+use common::types::{EventType, MagnitudeType};
 use diesel::prelude::*;
 
 #[derive(Queryable, Selectable, Insertable)]
@@ -6,12 +7,12 @@ use diesel::prelude::*;
 #[diesel(check_for_backend(diesel::pg::Pg))]
 pub struct EarthquakeEventModel {
     pub mag: f64,
-    pub place: String,
+    pub place: Option<String>,
     pub time: Option<NaiveDateTime>,
     pub updated: Option<NaiveDateTime>,
     pub tsunami: i32,
     pub lon: f64,
     pub lat: f64,
-    pub mag_type: String,
-    pub event_type: String,
+    pub mag_type: Option<MagnitudeType>,
+    pub event_type: EventType,
 }
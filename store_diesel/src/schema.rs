@@ -4,11 +4,11 @@ diesel::table! {
     earthquake_events (id) {
         id -> Int4,
         mag -> Float8,
-        place -> Text,
+        place -> Nullable<Text>,
         time -> Nullable<Timestamptz>,
         updated -> Nullable<Timestamptz>,
         tsunami -> Int4,
-        mag_type -> Text,
+        mag_type -> Nullable<Text>,
         event_type -> Text,
         lon -> Float8,
         lat -> Float8,
@@ -0,0 +1,68 @@
+use common::earthquake_event::EarthquakeEvent;
+use common::types::{EventType, MagnitudeType, PagerAlert};
+use diesel::prelude::*;
+use diesel::sql_types::{Nullable, Text};
+use store_diesel::models::EarthquakeEventModel;
+use store_diesel::schema::earthquake_events;
+use store_diesel::{convert_to_model, establish_connection, insert_earthquake_events};
+
+fn event(mag_type: Option<MagnitudeType>) -> EarthquakeEvent {
+    EarthquakeEvent {
+        mag: 4.2,
+        mag_type,
+        event_type: EventType::QuarryBlast,
+        ..EarthquakeEvent::default()
+    }
+}
+
+#[test]
+fn events_without_a_magnitude_type_have_none() {
+    let models = convert_to_model(vec![event(Some(MagnitudeType::MbLg)), event(None)]);
+
+    assert_eq!(models[0].mag_type, Some(MagnitudeType::MbLg));
+    assert_eq!(models[1].mag_type, None);
+    assert_eq!(models[1].event_type, EventType::QuarryBlast);
+}
+
+#[test]
+#[ignore = "needs a migrated PostgreSQL database in DATABASE_URL"]
+fn enums_round_trip_through_text_columns() {
+    let connection = &mut establish_connection();
+
+    for mag_type in [MagnitudeType::MbLg, MagnitudeType::Other("mfa".to_string())] {
+        let selected: MagnitudeType = diesel::select(mag_type.clone().into_sql::<Text>())
+            .get_result(connection)
+            .unwrap();
+        assert_eq!(selected, mag_type);
+    }
+    let written: String = diesel::select(EventType::InducedOrTriggered.into_sql::<Text>())
+        .get_result(connection)
+        .unwrap();
+    assert_eq!(written, "induced or triggered event");
+    let read: PagerAlert = diesel::select("RED".into_sql::<Text>())
+        .get_result(connection)
+        .unwrap();
+    assert_eq!(read, PagerAlert::Red);
+    let missing: Option<MagnitudeType> =
+        diesel::select(None::<String>.into_sql::<Nullable<Text>>())
+            .get_result(connection)
+            .unwrap();
+    assert_eq!(missing, None);
+}
+
+#[test]
+#[ignore = "needs a migrated PostgreSQL database in DATABASE_URL"]
+fn stores_missing_magnitude_types_as_null() {
+    let connection = &mut establish_connection();
+
+    connection.test_transaction::<_, diesel::result::Error, _>(|connection| {
+        insert_earthquake_events(connection, convert_to_model(vec![event(None)]))?;
+        let stored: Vec<EarthquakeEventModel> = earthquake_events::table
+            .select(EarthquakeEventModel::as_select())
+            .load(connection)?;
+        let last = stored.last().unwrap();
+        assert_eq!(last.mag_type, None);
+        assert_eq!(last.event_type, EventType::QuarryBlast);
+        Ok(())
+    });
+}